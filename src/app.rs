@@ -1,20 +1,29 @@
 use std::io;
 
 use crossterm::event as cse;
+use crossterm::terminal::SetTitle;
 
 use ratatui::{
     Frame,
     DefaultTerminal,
-    layout::{Layout, Constraint, Rect},
+    layout::{Layout, Constraint, Flex, Rect},
     style::{Style, Color, Modifier},
-    widgets::{Paragraph, Block, Borders},
+    text::Span,
+    widgets::{Paragraph, Block, Borders, Wrap},
 };
 
-use crate::events::{AppEventSource, AppEvent, AppAction};
+use crate::dialog::{Dialog, DialogResult};
+use crate::events::{AppEventSource, AppEvent, AppAction, InputSource};
+use crate::layout::KeyboardLayout;
+use crate::settings::{Settings, CaretStyle, ErrorMode, GhostTarget, Pace};
 
 use super::page::{self, Page, PageHandleEvent, LoadablePage};
 
-const APP_NAME: &str = "Stamba";
+pub const APP_NAME: &str = "Stamba";
+
+/// Smallest terminal the pages are laid out for.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
 
 #[derive(Debug)]
 pub struct App {
@@ -23,19 +32,30 @@ pub struct App {
     pub exit: bool,
 
     pub app_events: Option<AppEventSource>,
+    pub settings: Settings,
+    /// Last title set on the terminal window.
+    title: String,
 
     pages: Vec<Box<dyn Page>>,
+    /// Open dialogs, the last one gets the keys.
+    dialogs: Vec<Dialog>,
 }
 
 impl Default for App {
     fn default() -> Self {
+        let settings = Settings::default();
+        let pages = vec![page::get_page(LoadablePage::MainMenu, &settings)];
+
         App {
             exit: false,
             debug: false,
             frame: 0,
 
             app_events: None,
-            pages: vec![page::get_page(LoadablePage::MainMenu)],
+            settings,
+            title: String::new(),
+            pages,
+            dialogs: Vec::new(),
         }
     }
 }
@@ -52,16 +72,84 @@ impl App {
         self.debug = debug;
         self
     }
+
+    pub fn error_mode(mut self, error_mode: ErrorMode) -> Self {
+        self.settings.error_mode = error_mode;
+        self
+    }
+
+    pub fn backtrack(mut self, backtrack: bool) -> Self {
+        self.settings.backtrack = backtrack;
+        self
+    }
+
+    pub fn ghost(mut self, ghost: Option<GhostTarget>) -> Self {
+        self.settings.ghost = ghost;
+        self
+    }
+
+    pub fn pace(mut self, pace: Option<Pace>) -> Self {
+        self.settings.pace = pace;
+        self
+    }
+
+    pub fn name(mut self, name: Option<String>) -> Self {
+        self.settings.name = name;
+        self
+    }
+
+    pub fn layout(mut self, layout: KeyboardLayout) -> Self {
+        self.settings.layout = layout;
+        self
+    }
+
+    pub fn keyboard(mut self, keyboard: bool) -> Self {
+        self.settings.keyboard = keyboard;
+        self
+    }
+
+    pub fn caret(mut self, caret: CaretStyle) -> Self {
+        self.settings.caret = caret;
+        self
+    }
+
+    pub fn blink(mut self, blink: bool) -> Self {
+        self.settings.blink = blink;
+        self
+    }
+
+    pub fn focus(mut self, focus: bool) -> Self {
+        self.settings.focus = focus;
+        self
+    }
+
+    pub fn open(mut self, page: LoadablePage) -> Self {
+        self.go_to_page(page);
+        self
+    }
+
+    pub fn forgive_corrections(mut self, forgive: bool) -> Self {
+        self.settings.forgive_corrections = forgive;
+        self
+    }
 }
 
 // app loop
 impl App {
-    pub async fn init(&mut self) -> io::Result<()> {
-        self.app_events = Some(AppEventSource::init().await);
-
+    pub async fn init(&mut self, input: Box<dyn InputSource>) -> io::Result<()> {
+        self.attach(AppEventSource::init(input).await);
         Ok(())
     }
 
+    /// Start receiving events from the given source.
+    pub fn attach(&mut self, app_events: AppEventSource) {
+        for page in self.pages.iter_mut() {
+            page.on_load(app_events.get_dispatcher());
+        }
+
+        self.app_events = Some(app_events);
+    }
+
     pub async fn shutdown(&mut self) -> io::Result<()> {
         if let Some(mut events) = self.app_events.take() {
             events.shutdown().await;
@@ -77,8 +165,13 @@ impl App {
         // Main Loop
         while !self.exit {
             terminal.draw(|frame| self.draw(frame))?;
+            self.update_title(terminal)?;
             let events = self.app_events.as_mut().unwrap().collect_events().await;
 
+            if events.is_empty() {
+                break;
+            }
+
             for event in events.into_iter() {
                 self.handle_event(event)?;
             }
@@ -87,7 +180,19 @@ impl App {
         Ok(())
     }
 
-    fn handle_event(&mut self, event: AppEvent) -> io::Result<()> {
+    pub(crate) fn handle_event(&mut self, event: AppEvent) -> io::Result<()> {
+        if !self.dialogs.is_empty() {
+            match &event {
+                AppEvent::Crossterm(cse::Event::Key(key_event)) => {
+                    self.handle_dialog_key(key_event);
+                    return Ok(())
+                },
+                // Nothing under a dialog can be clicked.
+                AppEvent::Crossterm(cse::Event::Mouse(_)) => return Ok(()),
+                _ => {},
+            }
+        }
+
         let dispatcher = self.app_events.as_ref().unwrap().get_dispatcher();
         let page = self.get_active_page_mut();
 
@@ -102,7 +207,8 @@ impl App {
             AppEvent::App(action) => {
                 self.handle_action(action);
                 Ok(())
-            }
+            },
+            AppEvent::Race(_) | AppEvent::Tick => Ok(()),
         }
     }
 
@@ -111,23 +217,58 @@ impl App {
             cse::Event::Key(key_event) if key_event.kind == cse::KeyEventKind::Press => {
                 self.handle_cse_key_event(key_event);
             },
+            // The terminal picks up the new size on the next draw and the
+            // pages lay themselves out again from their own state.
+            cse::Event::Resize(..) => {},
+            // Pages that take the mouse already consumed it.
+            cse::Event::Mouse(_) => {},
             _ => {}
         }
 
         Ok(())
     }
 
+    fn handle_dialog_key(&mut self, key_event: &cse::KeyEvent) {
+        if key_event.kind != cse::KeyEventKind::Press {
+            return;
+        }
+
+        // A second Ctrl+C quits whatever the dialog is.
+        if is_ctrl_c(key_event) {
+            self.exit = true;
+            return;
+        }
+
+        let Some(dialog) = self.dialogs.last_mut() else {
+            return;
+        };
+
+        match dialog.handle_key(key_event) {
+            DialogResult::Open => {},
+            DialogResult::Close => {
+                self.dialogs.pop();
+            },
+            DialogResult::Action(action) => {
+                self.dialogs.pop();
+                self.handle_action(action);
+            },
+        }
+    }
+
     fn handle_cse_key_event(&mut self, key_event: cse::KeyEvent) {
         match key_event.code {
-            cse::KeyCode::Char('c') => {
-                if key_event.modifiers.contains(cse::KeyModifiers::CONTROL) {
-                    self.exit = true;
-                }
+            _ if is_ctrl_c(&key_event) && self.get_active_page().is_busy() => {
+                self.dialogs.push(Dialog::confirm(
+                    "Quit?",
+                    "The run in progress will be lost.",
+                    AppAction::Exit,
+                ));
+            },
+            _ if is_ctrl_c(&key_event) => {
+                self.exit = true;
             },
             cse::KeyCode::Esc => {
-                if self.pages.len() > 1 {
-                    self.pages.pop();
-                }
+                self.go_back();
             },
             _ => {},
         }
@@ -137,6 +278,8 @@ impl App {
         match action {
             AppAction::GoTo(page) => { self.go_to_page(page) },
             AppAction::Exit => { self.exit = true },
+            AppAction::Back => { self.go_back() },
+            AppAction::Dialog(dialog) => { self.dialogs.push(dialog) },
         }
     }
 }
@@ -151,17 +294,49 @@ impl App {
         self.pages.last_mut().unwrap().as_mut()
     }
 
+    /// Close the active page, the main menu stays open.
+    fn go_back(&mut self) {
+        if self.pages.len() > 1 {
+            self.pages.pop();
+            self.get_active_page_mut().on_resume();
+        }
+    }
+
     fn go_to_page(&mut self, page_id: LoadablePage) {
-        let page = page::get_page(page_id);
+        let mut page = page::get_page(page_id, &self.settings);
+
+        if let Some(events) = &self.app_events {
+            page.on_load(events.get_dispatcher());
+        }
+
         self.pages.push(page);
     }
 }
 
 // Implement drawing stuff.
 impl App {
-    fn draw(&mut self, frame: &mut Frame) {
+    pub(crate) fn draw(&mut self, frame: &mut Frame) {
         self.frame = self.frame.wrapping_add(1);
 
+        let area = frame.area();
+
+        if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+            self.draw_too_small(frame, area);
+            return;
+        }
+
+        if self.get_active_page().fullscreen() {
+            self.draw_page(frame, area);
+        } else {
+            self.draw_with_chrome(frame, area);
+        }
+
+        for dialog in self.dialogs.iter_mut() {
+            dialog.draw(frame, area);
+        }
+    }
+
+    fn draw_with_chrome(&mut self, frame: &mut Frame, area: Rect) {
         let vertical = Layout::vertical([
             Constraint::Length(2),
             Constraint::Min(1),
@@ -172,13 +347,26 @@ impl App {
             header_area,
             page_area,
             footer_area
-        ] = vertical.areas::<3>(frame.area());
+        ] = vertical.areas::<3>(area);
 
         self.draw_header(frame, header_area);
         self.draw_page(frame, page_area);
         self.draw_footer(frame, footer_area);
     }
 
+    fn update_title(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let page = self.get_active_page();
+        let title = page.terminal_title()
+            .unwrap_or_else(|| format!("{APP_NAME} - {}", page.page_title()));
+
+        if title != self.title {
+            crossterm::execute!(terminal.backend_mut(), SetTitle(&title))?;
+            self.title = title;
+        }
+
+        Ok(())
+    }
+
     fn draw_header(&self, frame: &mut Frame, header_space: Rect) {
         let [header, line] = Layout::vertical([
             Constraint::Length(1),
@@ -188,10 +376,17 @@ impl App {
         let border = Block::new().borders(Borders::BOTTOM);
         frame.render_widget(border, line);
 
+        let mut status = self.get_active_page().header();
+
+        if self.debug {
+            let separator = if status.spans.is_empty() { "" } else { " " };
+            status.spans.push(Span::raw(format!("{separator}{}", self.frame)));
+        }
+
         let constraints = [
             Constraint::Min(APP_NAME.len() as u16 + 2),
             Constraint::Fill(1),
-            Constraint::Min(1),
+            Constraint::Min(status.width().max(1) as u16),
         ];
 
         let horizontal = Layout::horizontal(constraints)
@@ -212,25 +407,50 @@ impl App {
 
         frame.render_widget(page_title, horizontal[1]);
 
-        if self.debug {
-            let debug_text = Paragraph::new(self.frame.to_string())
-                .right_aligned();
-
-            frame.render_widget(debug_text, horizontal[2]);
-        }
+        frame.render_widget(Paragraph::new(status).right_aligned(), horizontal[2]);
     }
 
     fn draw_footer(&self, frame: &mut Frame, header: Rect) {
+        let hints = self.get_active_page().footer_hints().into_iter()
+            .chain([("Ctrl+C", "exit"), ("ESC", "back")])
+            .map(|(key, action)| format!("{key} to {action}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
         let footer_style = Style::default().fg(Color::Gray);
-        let footer_text = Paragraph::new("Ctrl+C to exit, ESC to back")
+        let footer_text = Paragraph::new(hints)
             .style(footer_style)
             .centered();
 
         frame.render_widget(footer_text, header);
     }
 
+    fn draw_too_small(&self, frame: &mut Frame, area: Rect) {
+        let message = format!(
+            "Too small: {}x{}\nNeed {MIN_WIDTH}x{MIN_HEIGHT}",
+            area.width,
+            area.height,
+        );
+
+        let [middle] = Layout::vertical([Constraint::Length(2)])
+            .flex(Flex::Center)
+            .areas::<1>(area);
+
+        let text = Paragraph::new(message)
+            .style(Style::default().fg(Color::Yellow))
+            .wrap(Wrap { trim: true })
+            .centered();
+
+        frame.render_widget(text, middle);
+    }
+
     fn draw_page(&mut self, frame: &mut Frame, area: Rect) {
         let active_page = self.pages.last_mut().unwrap();
         active_page.draw(frame, area);
     }
 }
+
+fn is_ctrl_c(key_event: &cse::KeyEvent) -> bool {
+    key_event.code == cse::KeyCode::Char('c')
+        && key_event.modifiers.contains(cse::KeyModifiers::CONTROL)
+}
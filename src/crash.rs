@@ -0,0 +1,62 @@
+use std::backtrace::Backtrace;
+use std::fmt::{Debug, Write as _};
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::storage;
+
+/// Message, location and backtrace of the last panic.
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// Remember panics so they end up in the crash log. The previous hook
+/// still runs, so the message is printed as usual.
+pub fn install_hook() {
+    let hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let report = format!("{info}\n\nbacktrace:\n{}", Backtrace::force_capture());
+
+        if let Ok(mut last) = LAST_PANIC.lock() {
+            *last = Some(report);
+        }
+
+        hook(info);
+    }));
+}
+
+/// Write a crash log with the app state and describe where it went.
+pub fn report(reason: &str, state: &dyn Debug) -> String {
+    match write_log(reason, state) {
+        Ok(path) => format!("{reason}\nCrash log written to {}", path.display()),
+        Err(err) => format!("{reason}\nCould not write crash log: {err}"),
+    }
+}
+
+fn write_log(reason: &str, state: &dyn Debug) -> io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut log = String::new();
+    let _ = writeln!(log, "stamba {} crash report", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(log, "time: {timestamp}");
+    let _ = writeln!(log, "reason: {reason}");
+
+    let last_panic = LAST_PANIC.lock().ok().and_then(|mut last| last.take());
+
+    if let Some(last_panic) = last_panic {
+        let _ = writeln!(log, "\npanic: {last_panic}");
+    }
+
+    let _ = writeln!(log, "\nstate:\n{state:#?}");
+
+    let path = storage::data_path(&format!("crash/{timestamp}.log"))?;
+    fs::write(&path, log)?;
+
+    Ok(path)
+}
@@ -0,0 +1,253 @@
+use crossterm::event as cse;
+
+use ratatui::Frame;
+use ratatui::layout::{Rect, Layout, Constraint, Flex};
+use ratatui::style::{Style, Modifier, Color};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Clear, List, ListState, Padding, Paragraph, Wrap};
+
+use crate::events::AppAction;
+
+/// Widest a dialog gets, narrower screens squeeze it.
+const DIALOG_WIDTH: u16 = 44;
+
+const STYLE_BORDER: Style = Style::new()
+    .fg(Color::Cyan);
+
+const STYLE_ERROR_BORDER: Style = Style::new()
+    .fg(Color::Red);
+
+const STYLE_HINT: Style = Style::new()
+    .fg(Color::DarkGray);
+
+const STYLE_INPUT_CURSOR: Style = Style::new()
+    .add_modifier(Modifier::REVERSED);
+
+const SELECTED_STYLE: Style = Style::new()
+    .add_modifier(Modifier::BOLD)
+    .add_modifier(Modifier::UNDERLINED);
+
+#[derive(Debug, Clone)]
+pub enum DialogKind {
+    /// Yes or no, runs the action on yes.
+    Confirm(Box<AppAction>),
+    /// Positive number typed by the user, up to `max`.
+    Number {
+        value: String,
+        max: usize,
+        on_submit: fn(usize) -> AppAction,
+    },
+    /// One item out of a list.
+    Pick {
+        items: Vec<String>,
+        state: ListState,
+        on_pick: fn(usize) -> AppAction,
+    },
+    /// Error or notice, closed with Enter or Esc.
+    Message,
+}
+
+/// What the app should do with a dialog after a key.
+#[derive(Debug)]
+pub enum DialogResult {
+    Open,
+    Close,
+    Action(AppAction),
+}
+
+/// Modal popup drawn above the active page, it gets every key until it
+/// closes.
+#[derive(Debug, Clone)]
+pub struct Dialog {
+    pub title: String,
+    pub text: String,
+    pub kind: DialogKind,
+}
+
+// Constructors
+impl Dialog {
+    pub fn confirm(title: &str, text: &str, on_yes: AppAction) -> Self {
+        Dialog {
+            title: title.to_string(),
+            text: text.to_string(),
+            kind: DialogKind::Confirm(Box::new(on_yes)),
+        }
+    }
+
+    pub fn number(title: &str, text: &str, max: usize, on_submit: fn(usize) -> AppAction) -> Self {
+        Dialog {
+            title: title.to_string(),
+            text: text.to_string(),
+            kind: DialogKind::Number {
+                value: String::new(),
+                max,
+                on_submit,
+            },
+        }
+    }
+
+    pub fn pick(title: &str, items: Vec<String>, on_pick: fn(usize) -> AppAction) -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+
+        Dialog {
+            title: title.to_string(),
+            text: String::new(),
+            kind: DialogKind::Pick {
+                items,
+                state,
+                on_pick,
+            },
+        }
+    }
+
+    pub fn message(title: &str, text: &str) -> Self {
+        Dialog {
+            title: title.to_string(),
+            text: text.to_string(),
+            kind: DialogKind::Message,
+        }
+    }
+}
+
+impl Dialog {
+    pub fn handle_key(&mut self, key_event: &cse::KeyEvent) -> DialogResult {
+        if key_event.code == cse::KeyCode::Esc {
+            return DialogResult::Close
+        }
+
+        match &mut self.kind {
+            DialogKind::Confirm(on_yes) => match key_event.code {
+                cse::KeyCode::Char('y') | cse::KeyCode::Enter => {
+                    DialogResult::Action(on_yes.as_ref().clone())
+                },
+                cse::KeyCode::Char('n') => DialogResult::Close,
+                _ => DialogResult::Open,
+            },
+            DialogKind::Number { value, max, on_submit } => match key_event.code {
+                // Digits that would go past the maximum are not taken.
+                cse::KeyCode::Char(c) if c.is_ascii_digit() => {
+                    let longer = format!("{value}{c}");
+                    if longer.parse::<usize>().is_ok_and(|number| number <= *max) {
+                        *value = longer;
+                    }
+                    DialogResult::Open
+                },
+                cse::KeyCode::Backspace => {
+                    value.pop();
+                    DialogResult::Open
+                },
+                cse::KeyCode::Enter => match value.parse() {
+                    Ok(number) if number > 0 && number <= *max => DialogResult::Action(on_submit(number)),
+                    _ => DialogResult::Open,
+                },
+                _ => DialogResult::Open,
+            },
+            DialogKind::Pick { items, state, on_pick } => {
+                match key_event.code {
+                    cse::KeyCode::Char('k') | cse::KeyCode::Up => {
+                        state.select_previous();
+                    },
+                    cse::KeyCode::Char('j') | cse::KeyCode::Down => {
+                        let next = state.selected().map_or(0, |index| index + 1);
+                        state.select(Some(next.min(items.len().saturating_sub(1))));
+                    },
+                    cse::KeyCode::Enter => {
+                        if let Some(index) = state.selected() {
+                            return DialogResult::Action(on_pick(index))
+                        }
+                    },
+                    _ => {},
+                }
+
+                DialogResult::Open
+            },
+            DialogKind::Message => match key_event.code {
+                cse::KeyCode::Enter => DialogResult::Close,
+                _ => DialogResult::Open,
+            },
+        }
+    }
+
+    fn hint(&self) -> &'static str {
+        match self.kind {
+            DialogKind::Confirm(_) => "y yes  n no",
+            DialogKind::Number { .. } => "Enter ok  Esc cancel",
+            DialogKind::Pick { .. } => "j/k move  Enter pick  Esc cancel",
+            DialogKind::Message => "Enter close",
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let width = DIALOG_WIDTH.min(area.width);
+        let text_lines = wrapped_height(&self.text, width.saturating_sub(4));
+        let body_lines = match &self.kind {
+            DialogKind::Confirm(_) | DialogKind::Message => 0,
+            DialogKind::Number { .. } => 1,
+            DialogKind::Pick { items, .. } => items.len() as u16,
+        };
+        let gap = u16::from(text_lines > 0 && body_lines > 0);
+        // The hint goes on the bottom border.
+        let height = (2 + text_lines + gap + body_lines).min(area.height);
+
+        let [popup] = Layout::horizontal([Constraint::Length(width)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [popup] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(popup);
+
+        let border_style = match self.kind {
+            DialogKind::Message => STYLE_ERROR_BORDER,
+            _ => STYLE_BORDER,
+        };
+
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .padding(Padding::horizontal(1))
+            .title(format!(" {} ", self.title))
+            .title_bottom(Line::styled(format!(" {} ", self.hint()), STYLE_HINT).right_aligned());
+
+        let inner = block.inner(popup);
+        frame.render_widget(Clear, popup);
+        frame.render_widget(block, popup);
+
+        let [text_area, body_area] = Layout::vertical([
+            Constraint::Length(text_lines),
+            Constraint::Fill(1),
+        ]).spacing(gap).areas(inner);
+
+        let text = Paragraph::new(self.text.as_str())
+            .wrap(Wrap { trim: true });
+        frame.render_widget(text, text_area);
+
+        match &mut self.kind {
+            DialogKind::Number { value, .. } => {
+                let line = Line::from(vec![
+                    Span::from("> "),
+                    Span::from(value.as_str()),
+                    Span::styled(" ", STYLE_INPUT_CURSOR),
+                ]);
+                frame.render_widget(Paragraph::new(Text::from(line)), body_area);
+            },
+            DialogKind::Pick { items, state, .. } => {
+                let list = List::new(items.iter().map(String::as_str))
+                    .highlight_style(SELECTED_STYLE);
+                frame.render_stateful_widget(list, body_area, state);
+            },
+            DialogKind::Confirm(_) | DialogKind::Message => {},
+        }
+    }
+}
+
+/// Lines `text` takes when wrapped on words at `width` columns.
+fn wrapped_height(text: &str, width: u16) -> u16 {
+    if text.is_empty() {
+        return 0;
+    }
+
+    Paragraph::new(text)
+        .wrap(Wrap { trim: true })
+        .line_count(width.max(1)) as u16
+}
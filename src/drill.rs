@@ -0,0 +1,112 @@
+use std::collections::BTreeSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::settings::GameMode;
+
+const WORDS: &str = include_str!("../data/words.txt");
+const QUOTES: &str = include_str!("../data/quotes.txt");
+const CODE: &str = include_str!("../data/code.txt");
+
+/// Words per second of a timed run, more than anyone types.
+const TIMED_WORDS_PER_SECOND: usize = 4;
+
+/// Common english words drills are built from.
+pub fn common_words() -> Vec<&'static str> {
+    let unique: BTreeSet<&str> = WORDS.split_whitespace().collect();
+    unique.into_iter().collect()
+}
+
+/// `count` common words in random order.
+pub fn random_words(rng: &mut Rng, count: usize) -> String {
+    let words = common_words();
+
+    (0..count)
+        .map(|_| words[rng.below(words.len())])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quotes, one per line.
+pub fn quotes() -> Vec<&'static str> {
+    QUOTES.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Code snippets, separated by blank lines.
+pub fn code_snippets() -> Vec<&'static str> {
+    CODE.split("\n\n")
+        .map(|snippet| snippet.trim())
+        .filter(|snippet| !snippet.is_empty())
+        .collect()
+}
+
+/// Text to type in the given mode.
+pub fn game_text(mode: GameMode, rng: &mut Rng) -> String {
+    match mode {
+        GameMode::Timed(seconds) => random_words(rng, seconds as usize * TIMED_WORDS_PER_SECOND),
+        GameMode::Words(count) => random_words(rng, count),
+        GameMode::Quote => {
+            let quotes = quotes();
+            quotes[rng.below(quotes.len())].to_string()
+        },
+        GameMode::Code => {
+            let snippets = code_snippets();
+            snippets[rng.below(snippets.len())].to_string()
+        },
+    }
+}
+
+/// Small xorshift generator, good enough to shuffle drills around.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Zero would get xorshift stuck.
+        Rng { state: seed.max(1) }
+    }
+
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_nanos() as u64);
+
+        Rng::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+
+    /// Index picked with probability proportional to its weight.
+    pub fn weighted(&mut self, weights: &[f64]) -> usize {
+        let total: f64 = weights.iter().sum();
+
+        if total <= 0.0 {
+            return self.below(weights.len());
+        }
+
+        let mut target = (self.next_u64() as f64 / u64::MAX as f64) * total;
+
+        for (index, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return index;
+            }
+
+            target -= weight;
+        }
+
+        weights.len() - 1
+    }
+}
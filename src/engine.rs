@@ -0,0 +1,803 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::input::InputLine;
+
+/// How the session reacts to wrong keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// Wrong characters are typed and must be corrected before moving on.
+    #[default]
+    Normal,
+    /// Wrong characters are rejected and only counted.
+    StopOnLetter,
+    /// Wrong characters are typed, but space won't leave a wrong word.
+    StopOnWord,
+    /// Space always moves to the next word, wrong words are recorded.
+    Free,
+}
+
+impl ErrorMode {
+    pub const ALL: [ErrorMode; 4] = [
+        ErrorMode::Normal,
+        ErrorMode::StopOnLetter,
+        ErrorMode::StopOnWord,
+        ErrorMode::Free,
+    ];
+
+    /// Name used on the command line and in replay files.
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorMode::Normal => "normal",
+            ErrorMode::StopOnLetter => "stop-on-letter",
+            ErrorMode::StopOnWord => "stop-on-word",
+            ErrorMode::Free => "free",
+        }
+    }
+}
+
+impl FromStr for ErrorMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        ErrorMode::ALL.into_iter()
+            .find(|mode| mode.name() == value)
+            .ok_or_else(|| format!("unknown error mode \"{value}\""))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionOptions {
+    pub error_mode: ErrorMode,
+    /// Allow going back into previous words that contain errors.
+    pub backtrack: bool,
+    /// Only count mistakes that were left uncorrected.
+    pub forgive_corrections: bool,
+}
+
+/// Input understood by the session, independent of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Delete,
+    DeleteWord,
+    KillLine,
+    Left,
+    Right,
+    Home,
+    End,
+}
+
+/// Something that happened while feeding a key to the session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// A character was typed, `at` is the time into the run.
+    Keystroke {
+        typed: char,
+        expected: Option<char>,
+        correct: bool,
+        at: Duration,
+    },
+    WordCompleted { index: usize, correct: bool },
+    /// Backtracked into an earlier word.
+    WordReopened(usize),
+    Finished,
+}
+
+/// Copy of the session state at some moment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub current_word: usize,
+    pub input: String,
+    pub cursor: usize,
+    pub has_error: bool,
+    pub error_at_char: usize,
+    pub typed_words: Vec<String>,
+    pub elapsed: Duration,
+    pub started: bool,
+    pub done: bool,
+}
+
+/// Final numbers of a finished session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionResults {
+    pub wpm: f64,
+    pub accuracy: f64,
+    pub errors: usize,
+    pub keystrokes: usize,
+    pub words: usize,
+    pub duration: Duration,
+    /// Expected and typed word for every word left wrong.
+    pub wrong_words: Vec<(String, String)>,
+}
+
+/// Typing run over a text: tracks the input, errors and timing.
+///
+/// Time is passed in by the caller, so the session can be driven by real
+/// key presses as well as by replays and tests.
+#[derive(Debug, Clone)]
+pub struct TypingSession {
+    words: Vec<String>,
+    options: SessionOptions,
+
+    input: InputLine,
+    has_error: bool,
+    error_at_char: usize,
+    current_word: usize,
+
+    keystrokes: usize,
+    error_count: usize,
+    wrong_words: Vec<usize>,
+    typed_words: Vec<String>,
+    word_times: Vec<Duration>,
+
+    start_time: Option<Instant>,
+    end_time: Option<Instant>,
+
+    events: Vec<SessionEvent>,
+}
+
+impl TypingSession {
+    pub fn new(text: &str, options: SessionOptions) -> Self {
+        let words = text.split_whitespace()
+            .map(|s| s.to_string())
+            .collect();
+
+        TypingSession {
+            words,
+            options,
+
+            input: InputLine::new(),
+            has_error: false,
+            error_at_char: 0,
+            current_word: 0,
+
+            keystrokes: 0,
+            error_count: 0,
+            wrong_words: Vec::new(),
+            typed_words: Vec::new(),
+            word_times: Vec::new(),
+
+            start_time: None,
+            end_time: None,
+
+            events: Vec::new(),
+        }
+    }
+
+    pub fn start(&mut self, now: Instant) {
+        self.start_time = Some(now);
+
+        if self.words.is_empty() {
+            self.end_time = Some(now);
+        }
+    }
+
+    /// End the session before the text is done, like when time runs
+    /// out. Only the finished words count.
+    pub fn stop(&mut self, now: Instant) -> Vec<SessionEvent> {
+        if !self.is_started() || self.is_done() {
+            return Vec::new();
+        }
+
+        self.end_time = Some(now);
+        vec![SessionEvent::Finished]
+    }
+
+    /// Feed a key to the session and get what happened because of it.
+    pub fn input(&mut self, key: Key, now: Instant) -> Vec<SessionEvent> {
+        if !self.is_started() || self.is_done() {
+            return Vec::new();
+        }
+
+        match key {
+            Key::Char(c) => {
+                self.type_char(c, now);
+                return std::mem::take(&mut self.events);
+            },
+            Key::Backspace => {
+                if self.input.is_empty() && self.previous_word(now) {
+                    return std::mem::take(&mut self.events);
+                }
+
+                self.input.backspace();
+            },
+            Key::Delete => {
+                self.input.delete();
+            },
+            Key::DeleteWord => {
+                self.input.delete_word();
+            },
+            Key::KillLine => {
+                self.input.kill_line();
+            },
+            Key::Left => self.input.move_left(),
+            Key::Right => self.input.move_right(),
+            Key::Home => self.input.move_home(),
+            Key::End => self.input.move_end(),
+        }
+
+        self.verify_word(now);
+        std::mem::take(&mut self.events)
+    }
+}
+
+// State
+impl TypingSession {
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn text(&self) -> String {
+        self.words.join(" ")
+    }
+
+    pub fn options(&self) -> SessionOptions {
+        self.options
+    }
+
+    pub fn input_line(&self) -> &InputLine {
+        &self.input
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.has_error
+    }
+
+    pub fn error_at_char(&self) -> usize {
+        self.error_at_char
+    }
+
+    pub fn current_word(&self) -> usize {
+        self.current_word
+    }
+
+    pub fn typed_words(&self) -> &[String] {
+        &self.typed_words
+    }
+
+    /// Time into the run when each word was finished.
+    pub fn word_times(&self) -> &[Duration] {
+        &self.word_times
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.start_time.is_some()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.end_time.is_some()
+    }
+
+    pub fn is_word_correct(&self, index: usize) -> bool {
+        self.typed_words.get(index) == self.words.get(index)
+    }
+
+    /// Character the next key should type, `None` while there is an
+    /// error to correct first.
+    pub fn next_char(&self) -> Option<char> {
+        let cursor = self.input.cursor();
+
+        if self.current_word >= self.words.len()
+            || (self.has_error && self.error_at_char < cursor) {
+            return None;
+        }
+
+        self.expected_char(cursor)
+    }
+
+    /// Time into the run, or the whole run once it's done.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        let Some(start_time) = self.start_time else {
+            return Duration::ZERO;
+        };
+
+        self.end_time.unwrap_or(now).duration_since(start_time)
+    }
+
+    pub fn snapshot(&self, now: Instant) -> Snapshot {
+        Snapshot {
+            current_word: self.current_word,
+            input: self.input.as_str().to_string(),
+            cursor: self.input.cursor(),
+            has_error: self.has_error,
+            error_at_char: self.error_at_char,
+            typed_words: self.typed_words.clone(),
+            elapsed: self.elapsed(now),
+            started: self.is_started(),
+            done: self.is_done(),
+        }
+    }
+}
+
+// Stats
+impl TypingSession {
+    /// Wrong characters left in the finished words.
+    pub fn uncorrected_errors(&self) -> usize {
+        self.typed_words.iter()
+            .zip(&self.words)
+            .map(|(typed, expected)| {
+                let wrong = typed.chars()
+                    .zip(expected.chars())
+                    .filter(|(c1, c2)| c1 != c2)
+                    .count();
+
+                wrong + typed.chars().count().abs_diff(expected.chars().count())
+            })
+            .sum()
+    }
+
+    /// Errors reported in the results, depending on whether corrected
+    /// mistakes are forgiven.
+    pub fn errors(&self) -> usize {
+        if self.options.forgive_corrections {
+            self.uncorrected_errors()
+        } else {
+            self.error_count
+        }
+    }
+
+    /// Words per minute, counting only correctly typed words.
+    pub fn wpm(&self, now: Instant) -> f64 {
+        let words = self.current_word - self.wrong_words.len();
+        let minutes = self.elapsed(now).as_secs_f64() / 60.0;
+
+        if minutes == 0.0 {
+            return 0.0;
+        }
+
+        words as f64 / minutes
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        if self.keystrokes == 0 {
+            return 100.0;
+        }
+
+        let correct = self.keystrokes.saturating_sub(self.errors());
+        correct as f64 / self.keystrokes as f64 * 100.0
+    }
+
+    pub fn wrong_word_count(&self) -> usize {
+        self.wrong_words.len()
+    }
+
+    pub fn results(&self) -> Option<SessionResults> {
+        let end_time = self.end_time?;
+
+        let wrong_words = (0..self.typed_words.len())
+            .filter(|&index| !self.is_word_correct(index))
+            .map(|index| (self.words[index].clone(), self.typed_words[index].clone()))
+            .collect();
+
+        Some(SessionResults {
+            wpm: self.wpm(end_time),
+            accuracy: self.accuracy(),
+            errors: self.errors(),
+            keystrokes: self.keystrokes,
+            words: self.current_word,
+            duration: self.elapsed(end_time),
+            wrong_words,
+        })
+    }
+}
+
+// Typing logic
+impl TypingSession {
+    fn is_last_word(&self) -> bool {
+        self.current_word + 1 == self.words.len()
+    }
+
+    /// Character expected at the given input position, including the
+    /// space that separates words.
+    fn expected_char(&self, index: usize) -> Option<char> {
+        let current_word = &self.words[self.current_word];
+
+        current_word.chars()
+            .chain((!self.is_last_word()).then_some(' '))
+            .nth(index)
+    }
+
+    fn next_word(&mut self, now: Instant) {
+        let mut typed = self.input.take();
+
+        if typed.ends_with(' ') {
+            typed.pop();
+        }
+
+        let index = self.current_word;
+        let correct = typed == self.words[index];
+
+        self.typed_words.push(typed);
+        self.word_times.push(self.elapsed(now));
+        self.current_word += 1;
+        self.has_error = false;
+        self.events.push(SessionEvent::WordCompleted { index, correct });
+
+        if self.current_word == self.words.len() {
+            self.end_time = Some(now);
+            self.events.push(SessionEvent::Finished);
+        }
+    }
+
+    /// Go back into the previous word if it was left with errors.
+    fn previous_word(&mut self, now: Instant) -> bool {
+        if !self.options.backtrack || self.current_word == 0 {
+            return false;
+        }
+
+        let previous = self.current_word - 1;
+
+        if self.is_word_correct(previous) {
+            return false;
+        }
+
+        self.current_word = previous;
+        self.input.set(self.typed_words.pop().unwrap_or_default());
+        self.word_times.pop();
+        self.wrong_words.retain(|&index| index != previous);
+        self.events.push(SessionEvent::WordReopened(previous));
+        self.verify_word(now);
+        true
+    }
+
+    fn type_char(&mut self, c: char, now: Instant) {
+        // A stray space does nothing in free mode, so it isn't a mistake.
+        if self.options.error_mode == ErrorMode::Free && c == ' ' && self.input.is_empty() {
+            return;
+        }
+
+        self.keystrokes += 1;
+
+        let cursor = self.input.cursor();
+        let expected = self.expected_char(cursor);
+        let correct = (!self.has_error || self.error_at_char > cursor)
+            && expected == Some(c);
+
+        if !correct {
+            self.error_count += 1;
+        }
+
+        self.events.push(SessionEvent::Keystroke {
+            typed: c,
+            expected,
+            correct,
+            at: self.elapsed(now),
+        });
+
+        match self.options.error_mode {
+            ErrorMode::StopOnLetter if !correct => return,
+            ErrorMode::StopOnWord if !correct && c == ' ' => return,
+            ErrorMode::Free if c == ' ' => {
+                if self.input.as_str() != self.words[self.current_word] {
+                    self.wrong_words.push(self.current_word);
+                }
+
+                self.next_word(now);
+                return;
+            },
+            _ => {},
+        }
+
+        self.input.insert(c);
+        self.verify_word(now);
+    }
+
+    fn verify_word(&mut self, now: Instant) {
+        let current_word = &self.words[self.current_word];
+        let has_next = !self.is_last_word();
+
+        if has_next && self.input.as_str() == format!("{current_word} ") {
+            self.next_word(now);
+            return;
+        }
+
+        if !has_next && self.input.as_str() == current_word {
+            self.next_word(now);
+            return;
+        }
+
+        let iter = self.input.as_str().chars()
+            .zip(current_word.chars())
+            .enumerate();
+
+        self.has_error = false;
+
+        for (index, (c1, c2)) in iter {
+            if c1 != c2 {
+                self.has_error = true;
+                self.error_at_char = index;
+                return;
+            }
+        }
+
+        // Anything typed past the end of the word is overflow.
+        let word_len = current_word.chars().count();
+
+        if self.input.len() > word_len {
+            self.has_error = true;
+            self.error_at_char = word_len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(text: &str, error_mode: ErrorMode) -> (TypingSession, Instant) {
+        let options = SessionOptions {
+            error_mode,
+            ..Default::default()
+        };
+
+        let now = Instant::now();
+        let mut session = TypingSession::new(text, options);
+        session.start(now);
+
+        (session, now)
+    }
+
+    fn type_str(session: &mut TypingSession, text: &str, now: Instant) -> Vec<SessionEvent> {
+        text.chars()
+            .flat_map(|c| session.input(Key::Char(c), now))
+            .collect()
+    }
+
+    #[test]
+    fn completes_text() {
+        let (mut session, now) = session("hello world", ErrorMode::Normal);
+        let events = type_str(&mut session, "hello world", now);
+
+        assert!(session.is_done());
+        assert_eq!(session.typed_words(), ["hello", "world"]);
+        assert!(events.contains(&SessionEvent::WordCompleted { index: 0, correct: true }));
+        assert_eq!(events.last(), Some(&SessionEvent::Finished));
+
+        let results = session.results().unwrap();
+        assert_eq!(results.errors, 0);
+        assert_eq!(results.accuracy, 100.0);
+        assert!(results.wrong_words.is_empty());
+    }
+
+    #[test]
+    fn ignores_input_before_start() {
+        let mut session = TypingSession::new("hello", SessionOptions::default());
+        let events = session.input(Key::Char('h'), Instant::now());
+
+        assert!(events.is_empty());
+        assert!(session.input_line().is_empty());
+    }
+
+    #[test]
+    fn empty_text_is_done_on_start() {
+        let (mut session, now) = session("  ", ErrorMode::Normal);
+
+        assert!(session.is_done());
+        assert!(session.input(Key::Char('a'), now).is_empty());
+        assert_eq!(session.results().unwrap().words, 0);
+    }
+
+    #[test]
+    fn normal_mode_keeps_wrong_chars() {
+        let (mut session, now) = session("hello world", ErrorMode::Normal);
+        type_str(&mut session, "hex", now);
+
+        assert!(session.has_error());
+        assert_eq!(session.error_at_char(), 2);
+        assert_eq!(session.input_line().as_str(), "hex");
+
+        session.input(Key::Backspace, now);
+        assert!(!session.has_error());
+
+        type_str(&mut session, "llo ", now);
+        assert_eq!(session.current_word(), 1);
+        assert_eq!(session.errors(), 1);
+    }
+
+    #[test]
+    fn stop_on_letter_rejects_wrong_chars() {
+        let (mut session, now) = session("ab cd", ErrorMode::StopOnLetter);
+        type_str(&mut session, "axb", now);
+
+        assert_eq!(session.input_line().as_str(), "ab");
+        assert!(!session.has_error());
+        assert_eq!(session.errors(), 1);
+    }
+
+    #[test]
+    fn stop_on_word_rejects_space_on_wrong_word() {
+        let (mut session, now) = session("ab cd", ErrorMode::StopOnWord);
+        type_str(&mut session, "ax ", now);
+
+        assert_eq!(session.current_word(), 0);
+        assert_eq!(session.input_line().as_str(), "ax");
+        assert_eq!(session.errors(), 2);
+    }
+
+    #[test]
+    fn free_mode_records_wrong_words() {
+        let (mut session, now) = session("ab cd ef", ErrorMode::Free);
+        type_str(&mut session, "ax cd e ", now);
+
+        assert!(session.is_done());
+        assert_eq!(session.wrong_word_count(), 2);
+
+        let results = session.results().unwrap();
+        assert_eq!(results.wrong_words, [
+            ("ab".to_string(), "ax".to_string()),
+            ("ef".to_string(), "e".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn free_mode_ignores_stray_spaces() {
+        let (mut session, now) = session("ab cd", ErrorMode::Free);
+        let events = type_str(&mut session, "  ab  ", now);
+
+        assert_eq!(session.current_word(), 1);
+        assert_eq!(session.accuracy(), 100.0);
+        assert_eq!(events.iter().filter(|event| matches!(event, SessionEvent::Keystroke { .. })).count(), 3);
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        let (mut session, now) = session("ab cd", ErrorMode::Normal);
+        type_str(&mut session, "abxy", now);
+
+        assert!(session.has_error());
+        assert_eq!(session.error_at_char(), 2);
+        assert_eq!(session.errors(), 2);
+    }
+
+    #[test]
+    fn backtrack_into_wrong_word() {
+        let options = SessionOptions {
+            error_mode: ErrorMode::Free,
+            backtrack: true,
+            ..Default::default()
+        };
+
+        let now = Instant::now();
+        let mut session = TypingSession::new("ab cd", options);
+        session.start(now);
+        type_str(&mut session, "ax ", now);
+
+        let events = session.input(Key::Backspace, now);
+        assert_eq!(events, [SessionEvent::WordReopened(0)]);
+        assert_eq!(session.current_word(), 0);
+        assert_eq!(session.input_line().as_str(), "ax");
+
+        session.input(Key::Backspace, now);
+        type_str(&mut session, "b cd", now);
+
+        assert!(session.is_done());
+        assert_eq!(session.wrong_word_count(), 0);
+    }
+
+    #[test]
+    fn forgiven_corrections() {
+        let options = SessionOptions {
+            forgive_corrections: true,
+            ..Default::default()
+        };
+
+        let now = Instant::now();
+        let mut session = TypingSession::new("ab", options);
+        session.start(now);
+        type_str(&mut session, "x", now);
+        session.input(Key::Backspace, now);
+        type_str(&mut session, "ab", now);
+
+        assert_eq!(session.errors(), 0);
+        assert_eq!(session.accuracy(), 100.0);
+    }
+
+    #[test]
+    fn timing_comes_from_the_caller() {
+        let (mut session, now) = session("ab cd", ErrorMode::Normal);
+        type_str(&mut session, "ab ", now + Duration::from_secs(3));
+        type_str(&mut session, "cd", now + Duration::from_secs(6));
+
+        assert_eq!(session.word_times(), [
+            Duration::from_secs(3),
+            Duration::from_secs(6),
+        ]);
+
+        let results = session.results().unwrap();
+        assert_eq!(results.duration, Duration::from_secs(6));
+        assert_eq!(results.wpm, 20.0);
+    }
+
+    #[test]
+    fn single_word_finishes_without_space() {
+        let (mut session, now) = session("word", ErrorMode::Normal);
+        let events = type_str(&mut session, "word", now);
+
+        assert!(session.is_done());
+        assert_eq!(events.last(), Some(&SessionEvent::Finished));
+        assert_eq!(session.typed_words(), ["word"]);
+    }
+
+    #[test]
+    fn error_on_last_char() {
+        let (mut session, now) = session("ab cd", ErrorMode::Normal);
+        type_str(&mut session, "ab cx", now);
+
+        assert!(!session.is_done());
+        assert!(session.has_error());
+        assert_eq!(session.error_at_char(), 1);
+
+        session.input(Key::Backspace, now);
+        type_str(&mut session, "d", now);
+        assert!(session.is_done());
+        assert_eq!(session.errors(), 1);
+    }
+
+    #[test]
+    fn non_ascii_words() {
+        let (mut session, now) = session("naïve café", ErrorMode::Normal);
+        type_str(&mut session, "naïx", now);
+
+        assert!(session.has_error());
+        assert_eq!(session.error_at_char(), 3);
+
+        session.input(Key::Backspace, now);
+        type_str(&mut session, "ve café", now);
+        assert!(session.is_done());
+        assert_eq!(session.typed_words(), ["naïve", "café"]);
+    }
+
+    #[test]
+    fn non_ascii_overflow() {
+        let (mut session, now) = session("é ab", ErrorMode::Normal);
+        type_str(&mut session, "éé", now);
+
+        assert!(session.has_error());
+        assert_eq!(session.error_at_char(), 1);
+    }
+
+    #[test]
+    fn next_char() {
+        let (mut session, now) = session("ab cd", ErrorMode::Normal);
+        assert_eq!(session.next_char(), Some('a'));
+
+        type_str(&mut session, "ab", now);
+        assert_eq!(session.next_char(), Some(' '));
+
+        type_str(&mut session, " x", now);
+        assert_eq!(session.next_char(), None);
+
+        session.input(Key::Backspace, now);
+        type_str(&mut session, "cd", now);
+        assert_eq!(session.next_char(), None);
+    }
+
+    #[test]
+    fn stop_counts_finished_words() {
+        let (mut session, now) = session("ab cd ef", ErrorMode::Normal);
+        type_str(&mut session, "ab cd e", now);
+
+        assert_eq!(session.stop(now), [SessionEvent::Finished]);
+        assert!(session.is_done());
+        assert!(session.stop(now).is_empty());
+        assert!(type_str(&mut session, "f", now).is_empty());
+
+        let results = session.results().unwrap();
+        assert_eq!(results.words, 2);
+    }
+
+    #[test]
+    fn snapshot_copies_state() {
+        let (mut session, now) = session("héllo", ErrorMode::Normal);
+        type_str(&mut session, "hé", now);
+        session.input(Key::Left, now);
+
+        let snapshot = session.snapshot(now);
+        assert_eq!(snapshot.input, "hé");
+        assert_eq!(snapshot.cursor, 1);
+        assert!(snapshot.started);
+        assert!(!snapshot.done);
+    }
+}
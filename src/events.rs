@@ -1,19 +1,34 @@
+use std::time::Duration;
+
 use crossterm::event::Event as CrosstermEvent;
-use futures::{FutureExt, StreamExt};
 use tokio::sync::mpsc;
 
+use crate::dialog::Dialog;
 use crate::page::LoadablePage;
+use crate::race::RaceEvent;
+
+mod input;
+
+pub use input::{InputSource, ScriptedInput, TerminalInput};
+
+const TICK_RATE: Duration = Duration::from_millis(50);
 
 #[derive(Debug, Clone)]
 pub enum AppAction {
     Exit,
     GoTo(LoadablePage),
+    /// Close the active page.
+    Back,
+    /// Open a dialog above the active page.
+    Dialog(Dialog),
 }
 
 #[derive(Debug, Clone)]
 pub enum AppEvent {
     Crossterm(CrosstermEvent),
     App(AppAction),
+    Race(RaceEvent),
+    Tick,
 }
 
 #[derive(Debug)]
@@ -24,14 +39,21 @@ pub struct AppEventSource {
 }
 
 impl AppEventSource {
-    pub async fn init() -> Self {
+    pub async fn init(input: Box<dyn InputSource>) -> Self {
+        let mut source = AppEventSource::detached();
+        source.event_watcher = Some(event_loop(source.sender.clone(), input));
+        source
+    }
+
+    /// Event source that isn't connected to the terminal, events only
+    /// come from dispatchers.
+    pub fn detached() -> Self {
         let (sender, receiver) = mpsc::unbounded_channel::<AppEvent>();
-        let evloop = event_loop(sender.clone());
 
         AppEventSource {
             sender,
             receiver,
-            event_watcher: Some(evloop),
+            event_watcher: None,
         }
     }
 
@@ -43,11 +65,24 @@ impl AppEventSource {
         }
     }
 
+    /// Wait for events and collect everything queued. Empty once the
+    /// channel is closed.
     pub async fn collect_events(&mut self) -> Vec<AppEvent> {
         let len = self.receiver.len().max(1);
         let mut events = Vec::with_capacity(len);
-        let collected = self.receiver.recv_many(&mut events, len).await;
-        assert!(len == collected, "Should callect all events.");
+        self.receiver.recv_many(&mut events, len).await;
+        events
+    }
+
+    /// Collect the events that are already queued without waiting.
+    #[cfg(test)]
+    pub fn pending_events(&mut self) -> Vec<AppEvent> {
+        let mut events = Vec::new();
+
+        while let Ok(event) = self.receiver.try_recv() {
+            events.push(event);
+        }
+
         events
     }
 
@@ -65,30 +100,67 @@ impl AppEventDispatcher {
         AppEventDispatcher { sender }
     }
 
+    /// Send an event to the app. Events sent after the app has shut
+    /// down are dropped.
     pub fn dispatch(&self, event: AppEvent) {
-        self.sender.send(event).unwrap();
+        let _ = self.sender.send(event);
     }
 }
 
-fn event_loop(sender: mpsc::UnboundedSender<AppEvent>) -> tokio::task::JoinHandle<()> {
+fn event_loop(
+    sender: mpsc::UnboundedSender<AppEvent>,
+    mut input: Box<dyn InputSource>,
+) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
-        // let mut interval = tokio::time::interval(Duration::from_millis(1000));
-        let mut cross_stream = crossterm::event::EventStream::new();
+        let mut interval = tokio::time::interval(TICK_RATE);
+        let mut input_done = false;
 
         loop {
-            let cse = cross_stream.next().fuse();
-
-            tokio::select! {
+            let event = tokio::select! {
                 _ = sender.closed() => {
                     break;
                 }
-                // _ = interval.tick() => {
-                //     sender.send(AppEvent::Tick);
-                // }
-                Some(Ok(event)) = cse => {
-                    sender.send(AppEvent::Crossterm(event)).unwrap();
+                _ = interval.tick() => AppEvent::Tick,
+                event = input.next_event(), if !input_done => {
+                    let Some(event) = event else {
+                        input_done = true;
+                        continue;
+                    };
+
+                    AppEvent::Crossterm(event)
                 }
+            };
+
+            if sender.send(event).is_err() {
+                break;
             }
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyCode;
+
+    #[tokio::test]
+    async fn scripted_events() {
+        let script = ScriptedInput::parse("type ab\nwait 10\nkey Enter").unwrap();
+        let mut source = AppEventSource::init(Box::new(script)).await;
+        let mut keys = Vec::new();
+
+        while keys.len() < 3 {
+            for event in source.collect_events().await {
+                if let AppEvent::Crossterm(CrosstermEvent::Key(key)) = event {
+                    keys.push(key.code);
+                }
+            }
+        }
+
+        assert_eq!(keys, [KeyCode::Char('a'), KeyCode::Char('b'), KeyCode::Enter]);
+
+        // Whatever was queued before the shutdown drains, then it stops.
+        source.shutdown().await;
+        while !source.collect_events().await.is_empty() {}
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use futures::StreamExt;
+use futures::future::BoxFuture;
+
+/// Where terminal events come from.
+///
+/// `next_event` must be cancel safe, the event loop drops the future
+/// whenever a tick comes first.
+pub trait InputSource: Send {
+    /// Next event, `None` once the source has nothing more to give.
+    fn next_event(&mut self) -> BoxFuture<'_, Option<Event>>;
+}
+
+/// Events from the real terminal.
+pub struct TerminalInput {
+    stream: crossterm::event::EventStream,
+}
+
+impl TerminalInput {
+    pub fn new() -> Self {
+        TerminalInput {
+            stream: crossterm::event::EventStream::new(),
+        }
+    }
+}
+
+impl InputSource for TerminalInput {
+    fn next_event(&mut self) -> BoxFuture<'_, Option<Event>> {
+        Box::pin(async move {
+            // A read error means the terminal is gone, polling again
+            // would only fail again.
+            self.stream.next().await?.ok()
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Wait(Duration),
+    Event(Event),
+}
+
+/// Events read from a script, one command per line:
+///
+/// ```text
+/// # comment
+/// key Enter
+/// type Hello world!
+/// wait 500
+/// key ctrl+c
+/// resize 80 24
+/// ```
+#[derive(Debug, Default)]
+pub struct ScriptedInput {
+    steps: VecDeque<Step>,
+    deadline: Option<tokio::time::Instant>,
+}
+
+impl ScriptedInput {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let context = |err: &dyn std::fmt::Display| format!("{}: {err}", path.display());
+        let content = fs::read_to_string(path)
+            .map_err(|err| io::Error::new(err.kind(), context(&err)))?;
+
+        ScriptedInput::parse(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, context(&err)))
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut steps = VecDeque::new();
+
+        for (number, line) in content.lines().enumerate() {
+            let bad_line = || format!("bad script line {}: {line}", number + 1);
+            let line = line.trim_start();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+
+            match command {
+                "key" => {
+                    let key = parse_key(arg).ok_or_else(bad_line)?;
+                    steps.push_back(Step::Event(Event::Key(key)));
+                },
+                "type" => {
+                    for c in arg.chars() {
+                        let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+                        steps.push_back(Step::Event(Event::Key(key)));
+                    }
+                },
+                "wait" => {
+                    let ms = arg.parse().map_err(|_| bad_line())?;
+                    steps.push_back(Step::Wait(Duration::from_millis(ms)));
+                },
+                "resize" => {
+                    let (width, height) = arg.split_once(' ').ok_or_else(bad_line)?;
+                    let width = width.parse().map_err(|_| bad_line())?;
+                    let height = height.parse().map_err(|_| bad_line())?;
+                    steps.push_back(Step::Event(Event::Resize(width, height)));
+                },
+                _ => return Err(bad_line()),
+            }
+        }
+
+        Ok(ScriptedInput {
+            steps,
+            deadline: None,
+        })
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn next_event(&mut self) -> BoxFuture<'_, Option<Event>> {
+        Box::pin(async move {
+            loop {
+                match self.steps.front()? {
+                    Step::Wait(duration) => {
+                        // Keep the deadline around so a dropped future
+                        // doesn't restart the wait.
+                        let now = tokio::time::Instant::now();
+                        let deadline = *self.deadline.get_or_insert(now + *duration);
+                        tokio::time::sleep_until(deadline).await;
+
+                        self.deadline = None;
+                        self.steps.pop_front();
+                    },
+                    Step::Event(event) => {
+                        let event = event.clone();
+                        self.steps.pop_front();
+                        return Some(event);
+                    },
+                }
+            }
+        })
+    }
+}
+
+/// Parse keys like `Enter`, `a` or `ctrl+c`.
+fn parse_key(key: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = key;
+
+    while let Some((modifier, after)) = rest.split_once('+')
+        && !modifier.is_empty() {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+        rest = after;
+    }
+
+    let mut chars = rest.chars();
+
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyEvent::new(KeyCode::Char(c), modifiers));
+    }
+
+    let code = match rest.to_lowercase().as_str() {
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        _ => return None,
+    };
+
+    Some(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Step {
+        Step::Event(Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    #[test]
+    fn parse_script() {
+        let script = ScriptedInput::parse(
+            "# start a game\nkey Enter\ntype hi\nwait 20\nkey ctrl+c\nresize 80 24\n"
+        ).unwrap();
+
+        assert_eq!(Vec::from(script.steps), [
+            key(KeyCode::Enter, KeyModifiers::NONE),
+            key(KeyCode::Char('h'), KeyModifiers::NONE),
+            key(KeyCode::Char('i'), KeyModifiers::NONE),
+            Step::Wait(Duration::from_millis(20)),
+            key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Step::Event(Event::Resize(80, 24)),
+        ]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(ScriptedInput::parse("key Nope").is_err());
+        assert!(ScriptedInput::parse("wait soon").is_err());
+        assert!(ScriptedInput::parse("jump").is_err());
+        assert!(ScriptedInput::parse("key +").is_ok());
+    }
+
+    #[tokio::test]
+    async fn waits_survive_cancellation() {
+        let mut script = ScriptedInput::parse("wait 100\nkey a").unwrap();
+        let mut deadline = None;
+
+        for _ in 0..3 {
+            let next = tokio::time::timeout(Duration::from_millis(10), script.next_event());
+            assert!(next.await.is_err());
+
+            deadline = deadline.or(script.deadline);
+            assert_eq!(script.deadline, deadline);
+        }
+
+        assert!(script.next_event().await.is_some());
+        assert!(script.next_event().await.is_none());
+    }
+}
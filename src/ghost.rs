@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use stamba::engine::TypingSession;
+
+use crate::keymap::session_key;
+use crate::replay::Replay;
+
+/// Word positions of a previous run over time, used as a pacing target.
+#[derive(Debug, Clone)]
+pub struct Ghost {
+    pub label: &'static str,
+    /// Moments when the ghost moved to another word, in order.
+    timeline: Vec<(Duration, usize)>,
+    finish: Option<Duration>,
+}
+
+impl Ghost {
+    /// Play the replay back without drawing it and note when each word
+    /// was reached.
+    pub fn from_replay(label: &'static str, replay: &Replay) -> Self {
+        let origin = Instant::now();
+        let mut session = TypingSession::new(&replay.text, replay.options());
+        let mut timeline = Vec::new();
+        let mut finish = None;
+        session.start(origin);
+
+        for key in &replay.keys {
+            let Some(input) = session_key(&key.key_event()) else {
+                continue;
+            };
+
+            let word = session.current_word();
+            session.input(input, origin + key.at);
+
+            if session.current_word() != word {
+                timeline.push((key.at, session.current_word()));
+            }
+
+            if session.is_done() {
+                finish = Some(key.at);
+                break;
+            }
+        }
+
+        Ghost {
+            label,
+            timeline,
+            finish,
+        }
+    }
+
+    /// Word the ghost is typing at the given time into the run.
+    pub fn word_at(&self, elapsed: Duration) -> usize {
+        self.timeline.iter()
+            .take_while(|(at, _)| *at <= elapsed)
+            .last()
+            .map_or(0, |(_, word)| *word)
+    }
+
+    /// Time it took the ghost to first reach the given word.
+    pub fn time_to(&self, word: usize) -> Option<Duration> {
+        if word == 0 {
+            return Some(Duration::ZERO);
+        }
+
+        self.timeline.iter()
+            .find(|(_, reached)| *reached >= word)
+            .map(|(at, _)| *at)
+    }
+
+    pub fn finish(&self) -> Option<Duration> {
+        self.finish
+    }
+}
+
+/// Signed difference in seconds, positive when `ours` is earlier.
+pub fn lead_secs(ours: Duration, theirs: Duration) -> f64 {
+    theirs.as_secs_f64() - ours.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ghost() -> Ghost {
+        Ghost {
+            label: "best",
+            timeline: vec![
+                (Duration::from_millis(500), 1),
+                (Duration::from_millis(900), 2),
+                // Backtracked into the previous word and out again.
+                (Duration::from_millis(1000), 1),
+                (Duration::from_millis(1200), 2),
+                (Duration::from_millis(1500), 3),
+            ],
+            finish: Some(Duration::from_millis(1500)),
+        }
+    }
+
+    #[test]
+    fn word_at_follows_the_timeline() {
+        let ghost = ghost();
+
+        assert_eq!(ghost.word_at(Duration::ZERO), 0);
+        assert_eq!(ghost.word_at(Duration::from_millis(499)), 0);
+        assert_eq!(ghost.word_at(Duration::from_millis(500)), 1);
+        assert_eq!(ghost.word_at(Duration::from_millis(1100)), 1);
+        assert_eq!(ghost.word_at(Duration::from_secs(10)), 3);
+    }
+
+    #[test]
+    fn time_to_is_the_first_arrival() {
+        let ghost = ghost();
+
+        assert_eq!(ghost.time_to(0), Some(Duration::ZERO));
+        assert_eq!(ghost.time_to(2), Some(Duration::from_millis(900)));
+        assert_eq!(ghost.time_to(3), Some(Duration::from_millis(1500)));
+        assert_eq!(ghost.time_to(4), None);
+        assert_eq!(lead_secs(Duration::from_secs(1), Duration::from_millis(1500)), 0.5);
+    }
+}
@@ -0,0 +1,400 @@
+//! Drives `App` with synthetic events and renders it to a `TestBackend`.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossterm::event as cse;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+
+use crate::app::App;
+use crate::events::{AppEvent, AppEventSource};
+use crate::storage;
+
+const WIDTH: u16 = 60;
+const HEIGHT: u16 = 14;
+
+/// Every harness gets its own data directory.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub struct Harness {
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+}
+
+impl Harness {
+    /// Builds the app once the data directory is set, pages read their
+    /// files as soon as they are created.
+    pub fn new(app: impl FnOnce() -> App) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("stamba-test-{}-{id}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        storage::set_data_dir(dir);
+
+        let mut app = app();
+        app.attach(AppEventSource::detached());
+
+        let backend = TestBackend::new(WIDTH, HEIGHT);
+        let terminal = Terminal::new(backend).unwrap();
+
+        Harness { app, terminal }
+    }
+
+    /// Resize the terminal and tell the app about it.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.terminal.backend_mut().resize(width, height);
+        self.send(AppEvent::Crossterm(cse::Event::Resize(width, height)));
+    }
+
+    /// Handle an event along with everything the pages dispatch in turn.
+    pub fn send(&mut self, event: AppEvent) {
+        self.app.handle_event(event).unwrap();
+
+        loop {
+            let events = self.app.app_events.as_mut().unwrap().pending_events();
+
+            if events.is_empty() {
+                break;
+            }
+
+            for event in events {
+                self.app.handle_event(event).unwrap();
+            }
+        }
+    }
+
+    pub fn key(&mut self, code: cse::KeyCode) {
+        self.key_with(code, cse::KeyModifiers::NONE);
+    }
+
+    pub fn key_with(&mut self, code: cse::KeyCode, modifiers: cse::KeyModifiers) {
+        let key = cse::KeyEvent::new(code, modifiers);
+        self.send(AppEvent::Crossterm(cse::Event::Key(key)));
+    }
+
+    pub fn mouse(&mut self, kind: cse::MouseEventKind, column: u16, row: u16) {
+        let mouse = cse::MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: cse::KeyModifiers::NONE,
+        };
+        self.send(AppEvent::Crossterm(cse::Event::Mouse(mouse)));
+    }
+
+    pub fn click(&mut self, column: u16, row: u16) {
+        self.mouse(cse::MouseEventKind::Down(cse::MouseButton::Left), column, row);
+    }
+
+    pub fn type_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.key(cse::KeyCode::Char(c));
+        }
+    }
+
+    /// Draw a frame and return its text, one line per row.
+    pub fn render(&mut self) -> String {
+        self.terminal.draw(|frame| self.app.draw(frame)).unwrap();
+
+        let buffer = self.terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+
+        buffer.content()
+            .chunks(width)
+            .map(|row| {
+                let line = row.iter().map(|cell| cell.symbol()).collect::<String>();
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Compare against `src/snapshots/{name}.snap`. `UPDATE_SNAPSHOTS=1`
+/// writes them instead, a missing snapshot fails otherwise.
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/snapshots")
+        .join(format!("{name}.snap"));
+    let actual = format!("{}\n", redact(actual));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let Ok(expected) = fs::read_to_string(&path) else {
+        panic!("Snapshot {name} is missing, run with UPDATE_SNAPSHOTS=1 to create it.\n--- actual\n{actual}");
+    };
+    assert!(expected == actual,
+        "Snapshot {name} differs.\n--- expected\n{expected}--- actual\n{actual}");
+}
+
+/// Hide numbers that depend on how fast the test ran.
+fn redact(text: &str) -> String {
+    let mut out = text.to_string();
+
+    for marker in ["WPM: ", "Time "] {
+        let mut rest = out.as_str();
+        let mut redacted = String::with_capacity(rest.len());
+
+        while let Some(index) = rest.find(marker) {
+            let (before, after) = rest.split_at(index + marker.len());
+            redacted.push_str(before);
+
+            let len = after.chars().take_while(|c| c.is_ascii_digit() || *c == ':').count();
+            if len > 0 {
+                redacted.push_str("##");
+            }
+            rest = &after[len..];
+        }
+
+        redacted.push_str(rest);
+        out = redacted;
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers, MouseEventKind};
+
+    #[test]
+    fn main_menu() {
+        let mut harness = Harness::new(App::new);
+        assert_snapshot("main_menu", &harness.render());
+    }
+
+    #[test]
+    fn game_before_start() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("g");
+
+        assert_snapshot("game_before_start", &harness.render());
+    }
+
+    #[test]
+    fn game_with_error() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("g");
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hello wox");
+
+        assert_snapshot("game_with_error", &harness.render());
+    }
+
+    #[test]
+    fn game_with_keyboard() {
+        let mut harness = Harness::new(|| App::new().keyboard(true));
+        harness.type_str("g");
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hello w");
+
+        assert_snapshot("game_with_keyboard", &harness.render());
+    }
+
+    #[test]
+    fn focus_mode() {
+        let mut harness = Harness::new(|| App::new().focus(true));
+        harness.type_str("g");
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hello w");
+
+        assert_snapshot("focus_mode", &harness.render());
+    }
+
+    #[test]
+    fn game_results() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("g");
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hello world! helx");
+        harness.key(KeyCode::Backspace);
+        harness.type_str("lo again.");
+
+        assert_snapshot("game_results", &harness.render());
+    }
+
+    #[test]
+    fn lessons() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("l");
+
+        assert_snapshot("lessons", &harness.render());
+    }
+
+    #[test]
+    fn resize_keeps_progress() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("g");
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hello wor");
+
+        harness.resize(20, 6);
+        assert_snapshot("too_small", &harness.render());
+
+        harness.resize(40, 12);
+        assert_snapshot("resized_game", &harness.render());
+    }
+
+    #[test]
+    fn esc_goes_back_to_menu() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("g");
+        harness.key(KeyCode::Esc);
+
+        assert_snapshot("main_menu", &harness.render());
+    }
+
+    #[test]
+    fn confirm_quit_mid_run() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("g");
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hel");
+
+        harness.key_with(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(!harness.app.exit);
+        assert_snapshot("confirm_quit", &harness.render());
+
+        // Keys go to the dialog, not the game underneath.
+        harness.type_str("ln");
+        assert!(!harness.app.exit);
+        harness.type_str("lo");
+        assert!(harness.render().contains("> Hello"));
+
+        harness.key_with(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        harness.key(KeyCode::Char('y'));
+        assert!(harness.app.exit);
+    }
+
+    #[test]
+    fn custom_length() {
+        let mut harness = Harness::new(App::new);
+        harness.key(KeyCode::BackTab);
+        harness.key(KeyCode::Enter);
+        assert_snapshot("custom_length_pick", &harness.render());
+
+        for _ in 0..3 {
+            harness.key(KeyCode::Down);
+        }
+        harness.key(KeyCode::Enter);
+        harness.type_str("12345");
+        assert!(harness.render().contains("> 123 "), "stops short of the maximum");
+        for _ in 0..3 {
+            harness.key(KeyCode::Backspace);
+        }
+        harness.type_str("3");
+        assert_snapshot("custom_length_number", &harness.render());
+
+        harness.key(KeyCode::Enter);
+        let screen = harness.render();
+        let text = screen.lines().nth(2).unwrap();
+        assert_eq!(text.split(' ').count(), 3, "{screen}");
+    }
+
+    #[test]
+    fn click_menu_item() {
+        let mut harness = Harness::new(App::new);
+        let screen = harness.render();
+        let row = screen.lines().position(|line| line.contains(" Lessons")).unwrap();
+        let column = screen.lines().nth(row).unwrap().find("Lessons").unwrap();
+
+        harness.mouse(MouseEventKind::ScrollDown, 0, 6);
+        harness.click(column as u16, row as u16);
+        assert_snapshot("lessons", &harness.render());
+    }
+
+    #[test]
+    fn results_buttons() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("g");
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hello world! hello again.");
+
+        let screen = harness.render();
+        let row = screen.lines().position(|line| line.contains(" Again ")).unwrap() as u16;
+
+        harness.click(1, row);
+        assert!(harness.render().contains("Press Enter to start."));
+
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hello world! hello again.");
+        harness.render();
+        harness.click(10, row);
+        let screen = harness.render();
+        assert!(screen.contains("Last run") && screen.contains("2 runs,"), "{screen}");
+    }
+
+    #[test]
+    fn home_modes() {
+        let mut harness = Harness::new(App::new);
+        harness.key(KeyCode::Tab);
+        harness.key(KeyCode::Right);
+        assert_snapshot("home_modes", &harness.render());
+
+        harness.key(KeyCode::BackTab);
+        harness.key(KeyCode::Right);
+        harness.key(KeyCode::Enter);
+        harness.key(KeyCode::Enter);
+        harness.type_str("a");
+        let screen = harness.render();
+        assert!(screen.contains("Time 1:00"), "counts down from 60s: {screen}");
+
+        // Back home with the same selection.
+        harness.key(KeyCode::Esc);
+        assert!(harness.render().contains("◀ 60s ▶"));
+
+        harness.type_str("v");
+        let screen = harness.render();
+        assert!(screen.contains("Stats") && screen.contains("Finger Stats"), "{screen}");
+        harness.key(KeyCode::Esc);
+        assert!(harness.render().contains("Quick Game"));
+    }
+
+    #[test]
+    fn finger_stats_skip_the_thumb() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("g");
+        harness.key(KeyCode::Enter);
+        harness.type_str("Hello world! hello again.");
+        harness.key(KeyCode::Esc);
+
+        harness.type_str("vf");
+        let screen = harness.render();
+        assert!(screen.contains("left index") && !screen.contains("thumb"), "{screen}");
+    }
+
+    #[test]
+    fn space_starts_the_picked_mode() {
+        let mut harness = Harness::new(App::new);
+        harness.key(KeyCode::Tab);
+        harness.key(KeyCode::Char(' '));
+        harness.key(KeyCode::Enter);
+        harness.type_str("a");
+
+        let screen = harness.render();
+        assert!(!screen.contains("Hello world!"), "starts the picked mode, not Quick Game: {screen}");
+    }
+
+    #[test]
+    fn enter_on_menu_item() {
+        let mut harness = Harness::new(App::new);
+        harness.type_str("j");
+        assert!(harness.render().contains("Enter to select"));
+
+        harness.type_str("j");
+        harness.key(KeyCode::Enter);
+        assert_snapshot("lessons", &harness.render());
+
+        // Back on the menu, Esc hands the keys to the mode bar again.
+        harness.key(KeyCode::Esc);
+        harness.key(KeyCode::Esc);
+        harness.key(KeyCode::Enter);
+        assert!(harness.render().contains("Press Enter to start."));
+    }
+}
@@ -0,0 +1,185 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::storage;
+
+const HISTORY_FILE: &str = "history.tsv";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Results of a finished run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunRecord {
+    /// Seconds since the unix epoch when the run finished.
+    pub timestamp: u64,
+    pub wpm: f64,
+    pub accuracy: f64,
+    pub words: usize,
+    pub errors: usize,
+    pub duration: Duration,
+}
+
+impl RunRecord {
+    pub fn now(wpm: f64, accuracy: f64, words: usize, errors: usize, duration: Duration) -> Self {
+        RunRecord {
+            timestamp: now_timestamp(),
+            wpm,
+            accuracy,
+            words,
+            errors,
+            duration,
+        }
+    }
+
+    fn serialize(&self) -> String {
+        format!(
+            "{}\t{:.2}\t{:.2}\t{}\t{}\t{}",
+            self.timestamp,
+            self.wpm,
+            self.accuracy,
+            self.words,
+            self.errors,
+            self.duration.as_millis(),
+        )
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+
+        Some(RunRecord {
+            timestamp: fields.next()?.parse().ok()?,
+            wpm: fields.next()?.parse().ok()?,
+            accuracy: fields.next()?.parse().ok()?,
+            words: fields.next()?.parse().ok()?,
+            errors: fields.next()?.parse().ok()?,
+            duration: Duration::from_millis(fields.next()?.parse().ok()?),
+        })
+    }
+}
+
+/// Finished runs, oldest first.
+///
+/// Stored as one tab separated record per line, so a broken line only
+/// loses that single run.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    pub runs: Vec<RunRecord>,
+}
+
+impl History {
+    pub fn load() -> io::Result<Self> {
+        let path = storage::data_dir().join(HISTORY_FILE);
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        let runs = content.lines()
+            .filter_map(RunRecord::parse)
+            .collect();
+
+        Ok(History { runs })
+    }
+
+    pub fn append(record: &RunRecord) -> io::Result<()> {
+        let path = storage::data_path(HISTORY_FILE)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        writeln!(file, "{}", record.serialize())
+    }
+
+    /// Average WPM of all recorded runs.
+    pub fn average_wpm(&self) -> Option<f64> {
+        if self.runs.is_empty() {
+            return None;
+        }
+
+        let total: f64 = self.runs.iter().map(|run| run.wpm).sum();
+        Some(total / self.runs.len() as f64)
+    }
+}
+
+/// Totals of the runs finished on one day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DaySummary {
+    pub runs: usize,
+    pub average_wpm: f64,
+    pub best_wpm: f64,
+    pub time: Duration,
+}
+
+impl History {
+    pub fn last(&self) -> Option<&RunRecord> {
+        self.runs.last()
+    }
+
+    /// Runs finished on the same UTC day as `now`, in seconds since the
+    /// unix epoch.
+    pub fn day_summary(&self, now: u64) -> DaySummary {
+        let day = now / SECONDS_PER_DAY;
+        let runs: Vec<&RunRecord> = self.runs.iter()
+            .filter(|run| run.timestamp / SECONDS_PER_DAY == day)
+            .collect();
+
+        if runs.is_empty() {
+            return DaySummary::default();
+        }
+
+        DaySummary {
+            runs: runs.len(),
+            average_wpm: runs.iter().map(|run| run.wpm).sum::<f64>() / runs.len() as f64,
+            best_wpm: runs.iter().map(|run| run.wpm).fold(0.0, f64::max),
+            time: runs.iter().map(|run| run.duration).sum(),
+        }
+    }
+}
+
+/// Seconds since the unix epoch.
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(timestamp: u64, wpm: f64, seconds: u64) -> RunRecord {
+        RunRecord {
+            timestamp,
+            wpm,
+            accuracy: 100.0,
+            words: 10,
+            errors: 0,
+            duration: Duration::from_secs(seconds),
+        }
+    }
+
+    #[test]
+    fn day_summary_counts_only_that_day() {
+        let day = 20_000 * SECONDS_PER_DAY;
+        let history = History {
+            runs: vec![
+                run(day - 1, 90.0, 30),
+                run(day + 10, 40.0, 20),
+                run(day + 5000, 60.0, 25),
+            ],
+        };
+
+        let summary = history.day_summary(day + 6000);
+        assert_eq!(summary.runs, 2);
+        assert_eq!(summary.average_wpm, 50.0);
+        assert_eq!(summary.best_wpm, 60.0);
+        assert_eq!(summary.time, Duration::from_secs(45));
+
+        assert_eq!(history.day_summary(day + SECONDS_PER_DAY).runs, 0);
+        assert_eq!(history.last().map(|run| run.wpm), Some(60.0));
+    }
+}
@@ -0,0 +1,236 @@
+/// Single line text input with a cursor and readline-like editing.
+///
+/// The cursor is a character index, so the line can hold any unicode
+/// text without splitting characters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputLine {
+    text: String,
+    cursor: usize,
+}
+
+impl InputLine {
+    pub fn new() -> Self {
+        InputLine::default()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Replace the text and move the cursor to the end.
+    pub fn set(&mut self, text: String) {
+        self.cursor = text.chars().count();
+        self.text = text;
+    }
+
+    /// Take the text out, leaving the line empty.
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.text)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index(self.cursor);
+        self.text.insert(index, c);
+        self.cursor += 1;
+    }
+
+    /// Delete the character before the cursor.
+    pub fn backspace(&mut self) -> Option<char> {
+        if self.cursor == 0 {
+            return None;
+        }
+
+        self.cursor -= 1;
+        let index = self.byte_index(self.cursor);
+        Some(self.text.remove(index))
+    }
+
+    /// Delete the character under the cursor.
+    pub fn delete(&mut self) -> Option<char> {
+        if self.cursor == self.len() {
+            return None;
+        }
+
+        let index = self.byte_index(self.cursor);
+        Some(self.text.remove(index))
+    }
+
+    /// Delete the word before the cursor, along with any whitespace
+    /// between the word and the cursor.
+    pub fn delete_word(&mut self) -> String {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut start = self.cursor;
+
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        self.drain(start, self.cursor)
+    }
+
+    /// Delete everything before the cursor.
+    pub fn kill_line(&mut self) -> String {
+        self.drain(0, self.cursor)
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.len();
+    }
+
+    /// Split the text around the cursor: before, under and after.
+    pub fn split_at_cursor(&self) -> (&str, &str, &str) {
+        let at = self.byte_index(self.cursor);
+        let next = self.byte_index(self.cursor + 1);
+
+        (&self.text[..at], &self.text[at..next], &self.text[next..])
+    }
+
+    fn drain(&mut self, start: usize, end: usize) -> String {
+        let range = self.byte_index(start)..self.byte_index(end);
+        let removed = self.text.drain(range).collect();
+        self.cursor = start;
+        removed
+    }
+
+    fn byte_index(&self, index: usize) -> usize {
+        self.text.char_indices()
+            .nth(index)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str, cursor: usize) -> InputLine {
+        let mut line = InputLine::new();
+        line.set(text.to_string());
+        line.cursor = cursor;
+        line
+    }
+
+    #[test]
+    fn insert_at_cursor() {
+        let mut input = InputLine::new();
+        input.insert('a');
+        input.insert('c');
+        input.move_left();
+        input.insert('b');
+
+        assert_eq!(input.as_str(), "abc");
+        assert_eq!(input.cursor(), 2);
+    }
+
+    #[test]
+    fn insert_unicode() {
+        let mut input = line("wrld", 1);
+        input.insert('ö');
+
+        assert_eq!(input.as_str(), "wörld");
+        assert_eq!(input.cursor(), 2);
+        assert_eq!(input.len(), 5);
+    }
+
+    #[test]
+    fn backspace_and_delete() {
+        let mut input = line("héllo", 2);
+
+        assert_eq!(input.backspace(), Some('é'));
+        assert_eq!(input.as_str(), "hllo");
+        assert_eq!(input.delete(), Some('l'));
+        assert_eq!(input.as_str(), "hlo");
+        assert_eq!(input.cursor(), 1);
+
+        input.move_home();
+        assert_eq!(input.backspace(), None);
+
+        input.move_end();
+        assert_eq!(input.delete(), None);
+    }
+
+    #[test]
+    fn delete_word() {
+        let mut input = line("hello big  world", 16);
+
+        assert_eq!(input.delete_word(), "world");
+        assert_eq!(input.delete_word(), "big  ");
+        assert_eq!(input.as_str(), "hello ");
+        assert_eq!(input.cursor(), 6);
+    }
+
+    #[test]
+    fn delete_word_in_middle() {
+        let mut input = line("hello world", 8);
+
+        assert_eq!(input.delete_word(), "wo");
+        assert_eq!(input.as_str(), "hello rld");
+        assert_eq!(input.cursor(), 6);
+    }
+
+    #[test]
+    fn kill_line() {
+        let mut input = line("hello world", 6);
+
+        assert_eq!(input.kill_line(), "hello ");
+        assert_eq!(input.as_str(), "world");
+        assert_eq!(input.cursor(), 0);
+    }
+
+    #[test]
+    fn cursor_movement_is_clamped() {
+        let mut input = line("ab", 0);
+
+        input.move_left();
+        assert_eq!(input.cursor(), 0);
+
+        input.move_right();
+        input.move_right();
+        input.move_right();
+        assert_eq!(input.cursor(), 2);
+    }
+
+    #[test]
+    fn split_at_cursor() {
+        assert_eq!(line("añb", 1).split_at_cursor(), ("a", "ñ", "b"));
+        assert_eq!(line("ab", 2).split_at_cursor(), ("ab", "", ""));
+    }
+
+    #[test]
+    fn take_resets_cursor() {
+        let mut input = line("abc", 3);
+
+        assert_eq!(input.take(), "abc");
+        assert!(input.is_empty());
+        assert_eq!(input.cursor(), 0);
+    }
+}
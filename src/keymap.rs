@@ -0,0 +1,31 @@
+//! Terminal keys to engine input, shared by the game and ghost runs.
+
+use crossterm::event as cse;
+
+use stamba::engine::Key;
+
+/// Map a terminal key to session input, readline style.
+pub fn session_key(event_key: &cse::KeyEvent) -> Option<Key> {
+    let word_edit = event_key.modifiers
+        .intersects(cse::KeyModifiers::CONTROL | cse::KeyModifiers::ALT);
+    let ctrl = event_key.modifiers.contains(cse::KeyModifiers::CONTROL);
+
+    let key = match event_key.code {
+        cse::KeyCode::Backspace if word_edit => Key::DeleteWord,
+        cse::KeyCode::Backspace => Key::Backspace,
+        cse::KeyCode::Delete => Key::Delete,
+        cse::KeyCode::Left => Key::Left,
+        cse::KeyCode::Right => Key::Right,
+        cse::KeyCode::Home => Key::Home,
+        cse::KeyCode::End => Key::End,
+        cse::KeyCode::Char('w') if ctrl => Key::DeleteWord,
+        cse::KeyCode::Char('u') if ctrl => Key::KillLine,
+        cse::KeyCode::Char('a') if ctrl => Key::Home,
+        cse::KeyCode::Char('e') if ctrl => Key::End,
+        cse::KeyCode::Char(_) if ctrl => return None,
+        cse::KeyCode::Char(c) => Key::Char(c),
+        _ => return None,
+    };
+
+    Some(key)
+}
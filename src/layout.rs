@@ -0,0 +1,261 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Keys per row in the main block of a layout.
+pub const COLUMNS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    Thumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+impl Finger {
+    pub const ALL: [Finger; 9] = [
+        Finger::LeftPinky,
+        Finger::LeftRing,
+        Finger::LeftMiddle,
+        Finger::LeftIndex,
+        Finger::Thumb,
+        Finger::RightIndex,
+        Finger::RightMiddle,
+        Finger::RightRing,
+        Finger::RightPinky,
+    ];
+
+    /// Finger that touch typists use for a column.
+    pub fn for_column(column: usize) -> Self {
+        match column {
+            0 => Finger::LeftPinky,
+            1 => Finger::LeftRing,
+            2 => Finger::LeftMiddle,
+            3 | 4 => Finger::LeftIndex,
+            5 | 6 => Finger::RightIndex,
+            7 => Finger::RightMiddle,
+            8 => Finger::RightRing,
+            _ => Finger::RightPinky,
+        }
+    }
+}
+
+impl fmt::Display for Finger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Finger::LeftPinky => "left pinky",
+            Finger::LeftRing => "left ring",
+            Finger::LeftMiddle => "left middle",
+            Finger::LeftIndex => "left index",
+            Finger::Thumb => "thumb",
+            Finger::RightIndex => "right index",
+            Finger::RightMiddle => "right middle",
+            Finger::RightRing => "right ring",
+            Finger::RightPinky => "right pinky",
+        };
+
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Row {
+    Top,
+    Home,
+    Bottom,
+}
+
+impl Row {
+    pub const ALL: [Row; 3] = [Row::Top, Row::Home, Row::Bottom];
+}
+
+/// Where a key sits in the main block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPosition {
+    pub row: Row,
+    pub column: usize,
+}
+
+impl KeyPosition {
+    pub fn finger(&self) -> Finger {
+        Finger::for_column(self.column)
+    }
+}
+
+/// The three letter rows of a keyboard layout.
+///
+/// Custom layouts are read from a file with an optional `name` line
+/// followed by three rows of ten keys, lines starting with `#` are
+/// skipped:
+///
+/// ```text
+/// name Colemak-DH
+/// qwfpbjluy;
+/// arstgmneio
+/// zxcdvkh,./
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardLayout {
+    pub name: String,
+    rows: [[char; COLUMNS]; 3],
+}
+
+const BUILTIN: &[(&str, [&str; 3])] = &[
+    ("QWERTY", ["qwertyuiop", "asdfghjkl;", "zxcvbnm,./"]),
+    ("Dvorak", ["',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"]),
+    ("Colemak", ["qwfpgjluy;", "arstdhneio", "zxcvbkm,./"]),
+    ("Colemak-DH", ["qwfpbjluy;", "arstgmneio", "zxcdvkh,./"]),
+    ("Workman", ["qdrwbjfup;", "ashtgyneoi", "zxmcvkl,./"]),
+];
+
+impl Default for KeyboardLayout {
+    fn default() -> Self {
+        KeyboardLayout::builtin("qwerty").unwrap()
+    }
+}
+
+impl KeyboardLayout {
+    fn from_rows(name: &str, rows: [&str; 3]) -> Option<Self> {
+        let mut keys = [[' '; COLUMNS]; 3];
+
+        for (row, line) in keys.iter_mut().zip(rows) {
+            let chars: Vec<char> = line.chars().collect();
+            *row = chars.try_into().ok()?;
+        }
+
+        Some(KeyboardLayout {
+            name: name.to_string(),
+            rows: keys,
+        })
+    }
+
+    /// Names of the built in layouts.
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(name, _)| *name)
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (name, rows) = BUILTIN.iter()
+            .find(|(builtin, _)| builtin.eq_ignore_ascii_case(name))?;
+
+        KeyboardLayout::from_rows(name, *rows)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let fallback = path.file_stem()
+            .map_or("Custom".into(), |stem| stem.to_string_lossy());
+
+        KeyboardLayout::parse(&content, &fallback)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Built in layout by name, otherwise a layout file.
+    pub fn open(name: &str) -> io::Result<Self> {
+        if let Some(layout) = KeyboardLayout::builtin(name) {
+            return Ok(layout);
+        }
+
+        KeyboardLayout::load(Path::new(name)).map_err(|err| {
+            let builtin = KeyboardLayout::builtin_names().collect::<Vec<_>>().join(", ");
+            io::Error::new(err.kind(), format!("layout {name}: {err} (built in: {builtin})"))
+        })
+    }
+
+    pub fn parse(content: &str, fallback_name: &str) -> Result<Self, String> {
+        let mut name = fallback_name.to_string();
+        let mut rows = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(layout_name) = line.strip_prefix("name ") {
+                name = layout_name.trim().to_string();
+            } else {
+                rows.push(line);
+            }
+        }
+
+        let rows: [&str; 3] = rows.try_into()
+            .map_err(|_| "a layout needs exactly three rows".to_string())?;
+
+        KeyboardLayout::from_rows(&name, rows)
+            .ok_or_else(|| format!("every layout row needs {COLUMNS} keys"))
+    }
+
+    pub fn key(&self, position: KeyPosition) -> char {
+        self.rows[position.row as usize][position.column]
+    }
+
+    pub fn row(&self, row: Row) -> &[char; COLUMNS] {
+        &self.rows[row as usize]
+    }
+
+    /// Position of the key that types `c`, ignoring case.
+    pub fn position(&self, c: char) -> Option<KeyPosition> {
+        let c = c.to_lowercase().next()?;
+
+        Row::ALL.into_iter().find_map(|row| {
+            let column = self.row(row).iter().position(|&key| key == c)?;
+            Some(KeyPosition { row, column })
+        })
+    }
+
+    pub fn finger(&self, c: char) -> Option<Finger> {
+        if c == ' ' {
+            return Some(Finger::Thumb);
+        }
+
+        self.position(c).map(|position| position.finger())
+    }
+
+    /// Short name usable in file names.
+    pub fn id(&self) -> String {
+        self.name.to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_layouts() {
+        for name in KeyboardLayout::builtin_names() {
+            assert!(KeyboardLayout::builtin(name).is_some(), "{name}");
+        }
+
+        let dvorak = KeyboardLayout::builtin("dvorak").unwrap();
+        assert_eq!(dvorak.position('u'), Some(KeyPosition { row: Row::Home, column: 3 }));
+        assert_eq!(dvorak.finger('U'), Some(Finger::LeftIndex));
+        assert_eq!(dvorak.finger(' '), Some(Finger::Thumb));
+        assert_eq!(dvorak.finger('1'), None);
+    }
+
+    #[test]
+    fn custom_layout() {
+        let layout = KeyboardLayout::parse(
+            "# mine\nname Mine\nqwfpbjluy;\narstgmneio\nzxcdvkh,./\n",
+            "fallback",
+        ).unwrap();
+
+        assert_eq!(layout.name, "Mine");
+        assert_eq!(layout, KeyboardLayout { name: "Mine".into(), ..KeyboardLayout::builtin("colemak-dh").unwrap() });
+        assert!(KeyboardLayout::parse("abc\ndef\nghi", "short").is_err());
+        assert!(KeyboardLayout::parse("qwertyuiop\nasdfghjkl;", "rows").is_err());
+    }
+}
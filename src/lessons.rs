@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+
+use crate::drill::{self, Rng};
+use crate::layout::{KeyPosition, KeyboardLayout, Row};
+use crate::storage;
+
+/// Words in a lesson drill.
+pub const LESSON_WORDS: usize = 25;
+
+/// Step of the curriculum, introducing a few new keys. Keys are given
+/// by position, so the same lessons work for every layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lesson {
+    pub name: &'static str,
+    /// Keys this lesson adds to the ones from earlier lessons.
+    pub keys: &'static [(Row, usize)],
+    pub target_wpm: f64,
+    pub target_accuracy: f64,
+}
+
+const fn lesson(name: &'static str, keys: &'static [(Row, usize)], target_wpm: f64) -> Lesson {
+    Lesson {
+        name,
+        keys,
+        target_wpm,
+        target_accuracy: 95.0,
+    }
+}
+
+/// Home row first, then the top and bottom rows.
+pub const LESSONS: &[Lesson] = &[
+    lesson("Home row: index fingers", &[(Row::Home, 3), (Row::Home, 6)], 10.0),
+    lesson("Home row: middle fingers", &[(Row::Home, 2), (Row::Home, 7)], 12.0),
+    lesson("Home row: ring fingers", &[(Row::Home, 1), (Row::Home, 8)], 14.0),
+    lesson("Home row: little fingers", &[(Row::Home, 0), (Row::Home, 9)], 15.0),
+    lesson("Home row: reaching in", &[(Row::Home, 4), (Row::Home, 5)], 16.0),
+    lesson("Top row: middle fingers", &[(Row::Top, 2), (Row::Top, 7)], 18.0),
+    lesson("Top row: index fingers", &[(Row::Top, 3), (Row::Top, 6)], 18.0),
+    lesson("Top row: reaching in", &[(Row::Top, 4), (Row::Top, 5)], 20.0),
+    lesson("Top row: ring fingers", &[(Row::Top, 1), (Row::Top, 8)], 20.0),
+    lesson("Top row: little fingers", &[(Row::Top, 0), (Row::Top, 9)], 20.0),
+    lesson("Bottom row: index fingers", &[(Row::Bottom, 3), (Row::Bottom, 6)], 22.0),
+    lesson("Bottom row: middle fingers", &[(Row::Bottom, 2), (Row::Bottom, 7)], 22.0),
+    lesson("Bottom row: ring fingers", &[(Row::Bottom, 1), (Row::Bottom, 8)], 24.0),
+    lesson("Bottom row: little fingers", &[(Row::Bottom, 0), (Row::Bottom, 9)], 24.0),
+    lesson("Bottom row: reaching in", &[(Row::Bottom, 4), (Row::Bottom, 5)], 25.0),
+];
+
+impl Lesson {
+    /// Characters of the new keys on a layout.
+    pub fn keys(&self, layout: &KeyboardLayout) -> Vec<char> {
+        self.keys.iter()
+            .map(|&(row, column)| layout.key(KeyPosition { row, column }))
+            .collect()
+    }
+
+    /// Every key practiced up to and including the lesson at `index`.
+    pub fn keys_up_to(index: usize, layout: &KeyboardLayout) -> BTreeSet<char> {
+        LESSONS.iter()
+            .take(index + 1)
+            .flat_map(|lesson| lesson.keys(layout))
+            .collect()
+    }
+
+    /// Drill for the lesson at `index`: real words made only of keys
+    /// learned so far, mixed with made up ones that lean on the new keys.
+    pub fn drill(index: usize, layout: &KeyboardLayout, rng: &mut Rng, count: usize) -> String {
+        let keys = Lesson::keys_up_to(index, layout);
+        let letters: Vec<char> = keys.iter()
+            .copied()
+            .filter(|key| key.is_alphabetic())
+            .collect();
+        let new_keys = LESSONS[index].keys(layout);
+
+        let words: Vec<&str> = drill::common_words().into_iter()
+            .filter(|word| word.chars().all(|c| keys.contains(&c)))
+            .filter(|word| word.chars().any(|c| new_keys.contains(&c)))
+            .collect();
+
+        (0..count)
+            .map(|_| {
+                if !words.is_empty() && rng.below(2) == 0 {
+                    return words[rng.below(words.len())].to_string();
+                }
+
+                let new_key = new_keys[rng.below(new_keys.len())];
+
+                // Nothing but punctuation learned so far.
+                if letters.is_empty() {
+                    return new_key.to_string();
+                }
+
+                let len = 2 + rng.below(4);
+                let mut word: Vec<char> = (0..len)
+                    .map(|_| letters[rng.below(letters.len())])
+                    .collect();
+
+                // Make sure the new keys show up, punctuation goes last.
+                if new_key.is_alphabetic() {
+                    word[rng.below(len)] = new_key;
+                } else {
+                    word.push(new_key);
+                }
+
+                word.into_iter().collect()
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Best result of a lesson.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LessonScore {
+    pub wpm: f64,
+    pub accuracy: f64,
+}
+
+impl LessonScore {
+    pub fn passes(&self, lesson: &Lesson) -> bool {
+        self.wpm >= lesson.target_wpm && self.accuracy >= lesson.target_accuracy
+    }
+}
+
+/// Best score of every lesson played on a layout, stored as
+/// `index\twpm\taccuracy` lines in `lessons/{layout}.tsv`.
+#[derive(Debug, Clone, Default)]
+pub struct LessonProgress {
+    pub scores: BTreeMap<usize, LessonScore>,
+}
+
+fn progress_file(layout: &KeyboardLayout) -> String {
+    format!("lessons/{}.tsv", layout.id())
+}
+
+impl LessonProgress {
+    pub fn load(layout: &KeyboardLayout) -> io::Result<Self> {
+        let path = storage::data_dir().join(progress_file(layout));
+
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(LessonProgress::parse(&content)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(LessonProgress::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, layout: &KeyboardLayout) -> io::Result<()> {
+        let content: String = self.scores.iter()
+            .map(|(index, score)| format!("{index}\t{:.2}\t{:.2}\n", score.wpm, score.accuracy))
+            .collect();
+
+        fs::write(storage::data_path(&progress_file(layout))?, content)
+    }
+
+    fn parse(content: &str) -> Self {
+        let scores = content.lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let index = fields.next()?.parse().ok()?;
+                let score = LessonScore {
+                    wpm: fields.next()?.parse().ok()?,
+                    accuracy: fields.next()?.parse().ok()?,
+                };
+
+                Some((index, score))
+            })
+            .collect();
+
+        LessonProgress { scores }
+    }
+
+    pub fn is_completed(&self, index: usize) -> bool {
+        self.scores.get(&index)
+            .zip(LESSONS.get(index))
+            .is_some_and(|(score, lesson)| score.passes(lesson))
+    }
+
+    /// The first lesson is always open, the rest once the one before it
+    /// is completed.
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        index == 0 || self.is_completed(index - 1)
+    }
+
+    /// Keep the score if it beats the best one so far. Returns whether
+    /// the lesson passed with it.
+    pub fn record(&mut self, index: usize, score: LessonScore) -> bool {
+        let best = self.scores.entry(index).or_default();
+        let lesson = &LESSONS[index];
+
+        let better = match (score.passes(lesson), best.passes(lesson)) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => score.wpm > best.wpm,
+        };
+
+        if better {
+            *best = score;
+        }
+
+        score.passes(lesson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drill_uses_learned_keys() {
+        let mut rng = Rng::new(3);
+
+        for name in KeyboardLayout::builtin_names() {
+            let layout = KeyboardLayout::builtin(name).unwrap();
+
+            for index in [0, 4, 9] {
+                let keys = Lesson::keys_up_to(index, &layout);
+                let new_keys = LESSONS[index].keys(&layout);
+                let drill = Lesson::drill(index, &layout, &mut rng, 50);
+
+                assert!(drill.split(' ').count() == 50);
+                assert!(drill.chars().all(|c| c == ' ' || keys.contains(&c)), "{drill}");
+                assert!(drill.chars().any(|c| new_keys.contains(&c)));
+            }
+        }
+
+        let dvorak = KeyboardLayout::builtin("dvorak").unwrap();
+        assert_eq!(LESSONS[0].keys(&dvorak), ['u', 'h']);
+    }
+
+    #[test]
+    fn passing_unlocks_next_lesson() {
+        let mut progress = LessonProgress::default();
+        assert!(progress.is_unlocked(0));
+        assert!(!progress.is_unlocked(1));
+
+        let slow = LessonScore { wpm: 5.0, accuracy: 100.0 };
+        assert!(!progress.record(0, slow));
+        assert!(!progress.is_unlocked(1));
+
+        let good = LessonScore { wpm: 30.0, accuracy: 98.0 };
+        assert!(progress.record(0, good));
+        assert!(progress.is_unlocked(1));
+
+        // A faster run with bad accuracy doesn't replace a pass.
+        progress.record(0, LessonScore { wpm: 60.0, accuracy: 50.0 });
+        assert_eq!(progress.scores[&0], good);
+
+        let parsed = LessonProgress::parse("0\t30.00\t98.00\n");
+        assert_eq!(parsed.scores, progress.scores);
+    }
+}
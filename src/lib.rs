@@ -0,0 +1,7 @@
+//! Typing engine of stamba, independent of the terminal UI.
+//!
+//! [`engine::TypingSession`] takes key input with timestamps and keeps
+//! track of the typed text, errors and timing of a run.
+
+pub mod engine;
+pub mod input;
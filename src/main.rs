@@ -1,9 +1,38 @@
 use clap::Parser;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::style::Print;
+use crossterm::terminal::SetTitle;
+use futures::FutureExt;
 use std::io;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 mod app;
+mod crash;
+mod dialog;
+mod drill;
 mod page;
 mod events;
+mod ghost;
+mod history;
+mod keymap;
+mod layout;
+mod lessons;
+mod practice;
+mod race;
+mod replay;
+mod settings;
+mod storage;
+
+#[cfg(test)]
+mod harness;
+
+use events::{InputSource, ScriptedInput, TerminalInput};
+use layout::KeyboardLayout;
+use page::LoadablePage;
+use race::{RaceAddr, RaceServer};
+use settings::{CaretStyle, ErrorModeArg, GameMode, GhostTarget, Pace};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -15,19 +44,150 @@ struct Args {
     #[arg(short, long, default_value = "home",
            value_enum, help = "The page to load on startup")]
     page: String,
+
+    /// How wrong keys are handled while typing
+    #[arg(short, long, value_enum, default_value_t = ErrorModeArg::Normal)]
+    error_mode: ErrorModeArg,
+
+    /// Allow backspacing into previous words that contain errors
+    #[arg(short, long)]
+    backtrack: bool,
+
+    /// Don't count corrected mistakes as errors
+    #[arg(short, long)]
+    forgive_corrections: bool,
+
+    /// Race against an earlier run of the same text
+    #[arg(short, long, value_enum)]
+    ghost: Option<GhostTarget>,
+
+    /// Show a pace caret at a WPM, or "avg" for your average
+    #[arg(long, value_name = "WPM")]
+    pace: Option<Pace>,
+
+    /// Play back a recorded run
+    #[arg(short, long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// Host a race lobby on host:port or unix:PATH and join it
+    #[arg(long, value_name = "ADDR", conflicts_with = "join")]
+    host: Option<RaceAddr>,
+
+    /// Text of the hosted races: quote, code, words or words:COUNT
+    #[arg(long, value_name = "MODE", default_value = "quote", requires = "host")]
+    race_mode: GameMode,
+
+    /// Join a race lobby on host:port or unix:PATH
+    #[arg(long, value_name = "ADDR")]
+    join: Option<RaceAddr>,
+
+    /// Name shown to other racers
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Keyboard layout: qwerty, dvorak, colemak, colemak-dh, workman or
+    /// a layout file
+    #[arg(short, long, value_name = "LAYOUT", default_value = "qwerty")]
+    layout: String,
+
+    /// Show an on-screen keyboard with the next key and finger
+    #[arg(short, long)]
+    keyboard: bool,
+
+    /// How the caret is drawn
+    #[arg(long, value_enum, default_value_t = CaretStyle::Block)]
+    caret: CaretStyle,
+
+    /// Blink the caret while idle
+    #[arg(long)]
+    blink: bool,
+
+    /// Focus mode: only the text, typed onto directly
+    #[arg(short = 'z', long)]
+    focus: bool,
+
+    /// Read input from a script instead of the keyboard
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
 }
 
+/// Save and restore the window title on terminals that keep a stack of
+/// them, the app changes it while running.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
 #[tokio::main]
-async fn main() -> io::Result<()> {
+async fn main() -> ExitCode {
     let args = Args::parse();
 
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("stamba: {err}");
+            ExitCode::FAILURE
+        },
+    }
+}
+
+async fn run(args: Args) -> io::Result<()> {
     let mut app = app::App::new()
-        .debug(args.debug);
+        .debug(args.debug)
+        .error_mode(args.error_mode.into())
+        .backtrack(args.backtrack)
+        .forgive_corrections(args.forgive_corrections)
+        .ghost(args.ghost)
+        .pace(args.pace)
+        .name(args.name)
+        .layout(KeyboardLayout::open(&args.layout)?)
+        .keyboard(args.keyboard)
+        .caret(args.caret)
+        .blink(args.blink)
+        .focus(args.focus);
+
+    if let Some(path) = args.replay {
+        app = app.open(LoadablePage::Replay(path));
+    }
+
+    let mut lobby = None;
+
+    if let Some(addr) = args.host {
+        let text = drill::game_text(args.race_mode, &mut drill::Rng::from_time());
+        let server = RaceServer::bind(&addr, text).await?;
+        lobby = Some(tokio::spawn(server.run()));
+        app = app.open(LoadablePage::Race(addr));
+    } else if let Some(addr) = args.join {
+        app = app.open(LoadablePage::Race(addr));
+    }
 
-    let mut terminal = ratatui::init();
-    app.init().await?;
-    app.run(&mut terminal).await?;
+    let input: Box<dyn InputSource> = match args.script {
+        Some(path) => Box::new(ScriptedInput::load(&path)?),
+        None => Box::new(TerminalInput::new()),
+    };
+
+    crash::install_hook();
+    let mut terminal = ratatui::try_init()?;
+
+    let result = AssertUnwindSafe(async {
+        crossterm::execute!(io::stdout(), Print(PUSH_TITLE), EnableMouseCapture)?;
+        app.init(input).await?;
+        app.run(&mut terminal).await
+    }).catch_unwind().await;
+
+    // Restore before anything gets printed, however the app ended.
+    // An empty title lets terminals without a title stack fall back to
+    // their default.
+    let _ = crossterm::execute!(io::stdout(), DisableMouseCapture, SetTitle(""), Print(POP_TITLE));
     ratatui::restore();
 
-    app.shutdown().await
+    // Stopping the lobby removes its unix socket.
+    if let Some(lobby) = lobby {
+        lobby.abort();
+        let _ = lobby.await;
+    }
+
+    match result {
+        Ok(Ok(())) => app.shutdown().await,
+        Ok(Err(err)) => Err(io::Error::new(err.kind(), crash::report(&err.to_string(), &app))),
+        Err(_) => Err(io::Error::other(crash::report("crashed", &app))),
+    }
 }
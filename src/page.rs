@@ -1,20 +1,48 @@
 use std::fmt::Debug;
+use std::path::PathBuf;
 
 use ratatui::Frame;
-use ratatui::layout::Rect;
+use ratatui::layout::{Position, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::ListState;
 
 use crate::events::{AppEvent, AppEventDispatcher};
+use crate::race::RaceAddr;
+use crate::settings::{GameMode, Settings};
 
 pub mod home;
-pub use home::MenuPage;
+pub use home::HomePage;
 
 pub mod game;
 pub use game::GamePage;
 
+pub mod replay;
+pub use replay::ReplayPage;
+
+pub mod race;
+pub use race::RacePage;
+
+pub mod lessons;
+pub use lessons::LessonsPage;
+
+pub mod fingers;
+pub use fingers::FingerStatsPage;
+
+pub mod keyboard;
+
+pub mod menu;
+
 #[derive(Debug, Clone)]
 pub enum LoadablePage {
     MainMenu,
     GamePage,
+    Practice,
+    Lessons,
+    Lesson(usize),
+    Game(GameMode),
+    FingerStats,
+    Replay(PathBuf),
+    Race(RaceAddr),
 }
 
 // pub struct PageInfo {
@@ -29,13 +57,30 @@ pub enum LoadablePage {
 //     },
 // ];
 
-pub fn get_page(page: LoadablePage) -> Box<dyn Page> {
+pub fn get_page(page: LoadablePage, settings: &Settings) -> Box<dyn Page> {
     match page {
-        LoadablePage::MainMenu => Box::new(MenuPage::default()),
-        LoadablePage::GamePage => Box::new(GamePage::default())
+        LoadablePage::MainMenu => Box::new(HomePage::new()),
+        LoadablePage::GamePage => Box::new(GamePage::with_settings(settings)),
+        LoadablePage::Practice => Box::new(GamePage::practice(settings)),
+        LoadablePage::Lessons => Box::new(LessonsPage::new(settings)),
+        LoadablePage::Lesson(index) => Box::new(GamePage::lesson(index, settings)),
+        LoadablePage::Game(mode) => Box::new(GamePage::mode(mode, settings)),
+        LoadablePage::FingerStats => Box::new(FingerStatsPage::new(settings)),
+        LoadablePage::Replay(path) => Box::new(ReplayPage::open(&path)),
+        LoadablePage::Race(addr) => Box::new(RacePage::new(addr, settings)),
     }
 }
 
+/// Index of the list item drawn at a screen position, for clicks.
+pub fn list_item_at(area: Rect, state: &ListState, len: usize, column: u16, row: u16) -> Option<usize> {
+    if !area.contains(Position::new(column, row)) {
+        return None;
+    }
+
+    let index = state.offset() + (row - area.y) as usize;
+    (index < len).then_some(index)
+}
+
 pub enum PageHandleEvent {
     None,
     Consume
@@ -47,8 +92,34 @@ pub trait Page: Debug {
     fn draw(&mut self, frame: &mut Frame, rect: Rect);
     fn handle_event(&mut self, dispatcher: AppEventDispatcher, event: &AppEvent) -> PageHandleEvent;
 
+    /// Work in progress that quitting would throw away.
+    fn is_busy(&self) -> bool {
+        false
+    }
+
+    // chrome drawn by the app around the page
+    /// Draw over the whole screen, without the header and footer.
+    fn fullscreen(&self) -> bool {
+        false
+    }
+    /// Shown on the right of the header, like a timer or mode badges.
+    fn header(&self) -> Line<'_> {
+        Line::default()
+    }
+    /// Keys the page handles as `(key, action)`, listed in the footer.
+    fn footer_hints(&self) -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
+    /// Title of the terminal window, defaults to the page title.
+    fn terminal_title(&self) -> Option<String> {
+        None
+    }
+
     // life cycle methods
-    // fn on_load(&mut self);
+    /// Called once the page is on the stack and events can be dispatched.
+    fn on_load(&mut self, _dispatcher: AppEventDispatcher) {}
+    /// Called when the page is active again after the one above it closed.
+    fn on_resume(&mut self) {}
     // fn on_unload(&mut self);
     // fn on_init(&mut);
 
@@ -0,0 +1,109 @@
+use ratatui::Frame;
+use ratatui::layout::{Rect, Layout, Constraint};
+use ratatui::style::{Style, Color};
+use ratatui::widgets::{LineGauge, Paragraph};
+
+use crate::events::{AppEventDispatcher, AppEvent};
+use crate::layout::{Finger, KeyboardLayout};
+use crate::practice::{PracticeStats, Stat};
+use crate::settings::Settings;
+use super::{Page, PageHandleEvent};
+
+const STYLE_BAR: Style = Style::new()
+    .fg(Color::Cyan);
+
+const STYLE_BAD_BAR: Style = Style::new()
+    .fg(Color::Red);
+
+/// Error rate above which a finger is drawn as a problem.
+const BAD_ERROR_RATE: f64 = 0.05;
+
+/// How much each finger is used on the layout and how well it does.
+#[derive(Debug)]
+pub struct FingerStatsPage {
+    layout: KeyboardLayout,
+    title: String,
+    stats: PracticeStats,
+}
+
+impl FingerStatsPage {
+    pub fn new(settings: &Settings) -> Self {
+        let layout = settings.layout.clone();
+
+        FingerStatsPage {
+            title: format!("Finger Stats ({})", layout.name),
+            stats: PracticeStats::load().unwrap_or_default(),
+            layout,
+        }
+    }
+}
+
+impl Page for FingerStatsPage {
+    fn on_resume(&mut self) {
+        self.stats = PracticeStats::load().unwrap_or_default();
+    }
+
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let fingers = self.stats.finger_stats(&self.layout);
+        let total: f64 = fingers.values().map(|stat| stat.count).sum();
+
+        let [info, rows_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(1),
+        ]).areas::<2>(rect);
+
+        if total == 0.0 {
+            frame.render_widget(Paragraph::new("Finish a run to see finger stats."), info);
+            return;
+        }
+
+        frame.render_widget(Paragraph::new("Share of keystrokes, errors and time per key."), info);
+
+        // Spaces aren't part of the key stats, so the thumb never has any.
+        let shown: Vec<Finger> = Finger::ALL.into_iter()
+            .filter(|finger| *finger != Finger::Thumb)
+            .collect();
+        let rows = Layout::vertical(
+            shown.iter().map(|_| Constraint::Length(1))
+        ).split(rows_area);
+
+        for (finger, row) in shown.iter().zip(rows.iter()) {
+            let stat = fingers.get(finger).copied().unwrap_or_default();
+            let latency = stat.avg_latency()
+                .map(|latency| format!("{:>4.0}ms", latency * 1000.0))
+                .unwrap_or_else(|| "    -".to_string());
+            let style = if stat.error_rate() > BAD_ERROR_RATE {
+                STYLE_BAD_BAR
+            } else {
+                STYLE_BAR
+            };
+
+            let gauge = LineGauge::default()
+                .filled_style(style)
+                .label(format!(
+                    "{:>12} {:>5.1}% err {latency}",
+                    finger.to_string(),
+                    error_percent(&stat),
+                ))
+                .ratio(stat.count / total);
+
+            frame.render_widget(gauge, *row);
+        }
+    }
+
+    fn handle_event(&mut self, _: AppEventDispatcher, _: &AppEvent) -> PageHandleEvent {
+        PageHandleEvent::None
+    }
+
+    fn page_title(&self) -> &str {
+        &self.title
+    }
+}
+
+fn error_percent(stat: &Stat) -> f64 {
+    if stat.count == 0.0 {
+        return 0.0;
+    }
+
+    stat.errors / stat.count * 100.0
+}
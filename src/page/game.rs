@@ -1,17 +1,46 @@
 use crossterm::event as cse;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use ratatui::Frame;
 use ratatui::text::{Text, Line, Span};
-use ratatui::layout::{Rect, Layout, Constraint};
+use ratatui::layout::{Rect, Layout, Constraint, Flex};
 use ratatui::style::{Style, Modifier, Stylize, Color};
-use ratatui::widgets::{Paragraph, Wrap, Block, Borders};
+use ratatui::widgets::{Paragraph, Block, Borders};
 
-use crate::events::{AppEventDispatcher, AppEvent};
+use crate::app::APP_NAME;
+use crate::drill::{self, Rng};
+use crate::events::{AppEventDispatcher, AppAction, AppEvent};
+use crate::ghost::{self, Ghost};
+use crate::history::{History, RunRecord};
+use crate::keymap::session_key;
+use crate::layout::KeyboardLayout;
+use crate::lessons::{Lesson, LessonProgress, LessonScore, LESSONS, LESSON_WORDS};
+use crate::practice::PracticeStats;
+use crate::replay::{self, Replay};
+use crate::storage;
+use crate::settings::{CaretStyle, ErrorMode, GameMode, GhostTarget, Pace, Settings};
+use stamba::engine::{SessionEvent, SessionOptions, TypingSession};
+use super::keyboard::{Keyboard, KEYBOARD_HEIGHT};
 use super::{Page, PageHandleEvent};
 
 // const DEFAULT_TEXT: &str = include_str!("../../data/example.txt");
-const DEFAULT_TEXT: &str = "Hello world! hello again.";
+pub const DEFAULT_TEXT: &str = "Hello world! hello again.";
+
+const PRACTICE_WORDS: usize = 30;
+
+/// Lines of text shown while typing, the rest scrolls.
+const VIEWPORT_LINES: usize = 3;
+
+/// How long a wrong key stays red on the keyboard.
+const WRONG_KEY_FLASH: Duration = Duration::from_millis(300);
+
+/// Widest the text gets in focus mode.
+const FOCUS_WIDTH: u16 = 64;
+
+/// Ticks the caret stays on or off while blinking.
+const BLINK_TICKS: u32 = 10;
+
+const CARET_BAR: &str = "▏";
 
 const STYLE_ACTIVE_WORD: Style = Style::new()
     .add_modifier(Modifier::UNDERLINED);
@@ -24,12 +53,49 @@ const STYLE_BAD_CURSOR: Style = STYLE_ACTIVE_WORD
     .bg(Color::Gray)
     .fg(Color::Red);
 
+const STYLE_UNDERLINE_CURSOR: Style = STYLE_ACTIVE_WORD
+    .fg(Color::Yellow)
+    .underline_color(Color::Yellow)
+    .add_modifier(Modifier::BOLD);
+
+const STYLE_BAD_UNDERLINE_CURSOR: Style = STYLE_UNDERLINE_CURSOR
+    .fg(Color::Red)
+    .underline_color(Color::Red);
+
+const STYLE_BAR_CURSOR: Style = Style::new()
+    .fg(Color::Yellow);
+
+const STYLE_BAD_BAR_CURSOR: Style = Style::new()
+    .fg(Color::Red);
+
 const STYLE_WRONG_CHARS: Style = STYLE_ACTIVE_WORD
     .fg(Color::Red);
 
 const STYLE_CORRECT_CHARS: Style = STYLE_ACTIVE_WORD
     .fg(Color::Green);
 
+const STYLE_OVERFLOW_CHARS: Style = STYLE_WRONG_CHARS
+    .add_modifier(Modifier::DIM);
+
+const STYLE_WRONG_WORD: Style = Style::new()
+    .fg(Color::Red)
+    .add_modifier(Modifier::UNDERLINED);
+
+const STYLE_BADGE: Style = Style::new()
+    .bg(Color::DarkGray)
+    .fg(Color::White);
+
+const STYLE_BUTTON: Style = Style::new()
+    .add_modifier(Modifier::REVERSED);
+
+const STYLE_GHOST: Style = Style::new()
+    .bg(Color::Magenta)
+    .fg(Color::Black);
+
+const STYLE_PACE: Style = Style::new()
+    .bg(Color::Cyan)
+    .fg(Color::Black);
+
 const STYLE_INPUT_PREFIX: Style = Style::new()
     .fg(Color::Cyan);
 
@@ -40,253 +106,1147 @@ const STYLE_INPUT: Style = Style::new()
     .add_modifier(Modifier::UNDERLINED)
     .fg(Color::Gray);
 
-#[derive(Debug)]
-pub struct GamePage {
-    pub text_state: GameText,
+const STYLE_INPUT_CURSOR: Style = STYLE_INPUT
+    .add_modifier(Modifier::REVERSED);
 
-    pub input_text: String,
-    pub has_error: bool,
-    pub error_at_char: usize,
-    pub current_word: usize,
+/// Clickable buttons under the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultButton {
+    Again,
+    Menu,
+}
 
-    pub started: bool,
-    pub done: bool,
+impl ResultButton {
+    const ALL: [ResultButton; 2] = [ResultButton::Again, ResultButton::Menu];
 
-    pub start_time: Option<Instant>,
-    pub end_time: Option<Instant>,
+    fn label(&self) -> &'static str {
+        match self {
+            ResultButton::Again => " Again ",
+            ResultButton::Menu => " Menu ",
+        }
+    }
 }
 
 #[derive(Debug)]
-pub struct GameText {
-    // pub raw: String,
-    pub words: Vec<String>,
+pub struct GamePage {
+    pub session: TypingSession,
+
+    pub replay: Replay,
+    pub record: bool,
+    pub save_error: Option<String>,
+
+    pub ghost: Option<Ghost>,
+    pub pace_wpm: Option<f64>,
+    pub layout: KeyboardLayout,
+    /// Show the on-screen keyboard.
+    pub keyboard: bool,
+    pub wrong_key: Option<(char, Instant)>,
+
+    pub caret: CaretStyle,
+    pub blink: bool,
+    /// Ticks since the last key, drives the blinking.
+    pub idle_ticks: u32,
+    /// Only the text on screen until the run is over.
+    pub focus: bool,
+    /// Offer to play again or go back once the run is over.
+    pub buttons: bool,
+    button_areas: Vec<(Rect, ResultButton)>,
+
+    /// Everything the session reported, for the practice stats.
+    pub events: Vec<SessionEvent>,
+    pub advice: Option<String>,
+
+    /// Run ends when this much time is up.
+    pub time_limit: Option<Duration>,
+
+    /// Index of the lesson being played, if any.
+    pub lesson: Option<usize>,
+    pub lesson_status: Option<String>,
 }
 
 impl GamePage {
-    pub fn new(text: String) -> Self {
-        let text = text.trim().to_string();
+    pub fn new(text: String, options: SessionOptions) -> Self {
+        let session = TypingSession::new(&text, options);
+        let replay = Replay::new(session.text(), options.error_mode, options.backtrack);
 
-        let words: Vec<String> = text.split_whitespace()
-            .map(|s| s.to_string())
-            .collect();
+        GamePage {
+            session,
+
+            replay,
+            record: true,
+            save_error: None,
+
+            ghost: None,
+            pace_wpm: None,
+            layout: KeyboardLayout::default(),
+            keyboard: false,
+            wrong_key: None,
+
+            caret: CaretStyle::default(),
+            blink: false,
+            idle_ticks: 0,
+            focus: false,
+            buttons: false,
+            button_areas: Vec::new(),
+
+            events: Vec::new(),
+            advice: None,
+
+            time_limit: None,
+
+            lesson: None,
+            lesson_status: None,
+        }
+    }
+
+    pub fn from_replay(replay: &Replay) -> Self {
+        let mut game = GamePage::new(replay.text.clone(), replay.options());
+        game.record = false;
+        game.time_limit = replay.limit;
+        game
+    }
+
+    pub fn start(&mut self, now: Instant) {
+        self.session.start(now);
+    }
+
+    /// Play the same text again.
+    pub fn restart(&mut self) {
+        let options = self.session.options();
+        self.session = TypingSession::new(&self.session.text(), options);
+        self.replay = Replay::new(self.session.text(), options.error_mode, options.backtrack);
+        self.replay.limit = self.time_limit;
+
+        self.save_error = None;
+        self.wrong_key = None;
+        self.idle_ticks = 0;
+        self.events.clear();
+        self.advice = None;
+        self.lesson_status = None;
+        self.button_areas.clear();
+    }
+
+    fn press_button(&mut self, dispatcher: AppEventDispatcher, button: ResultButton) {
+        match button {
+            ResultButton::Again => self.restart(),
+            ResultButton::Menu => dispatcher.dispatch(AppEvent::App(AppAction::Back)),
+        }
+    }
 
-        let game_text = GameText {
-            // raw: text.to_string(),
-            words,
+    fn handle_mouse(&mut self, dispatcher: AppEventDispatcher, event: &cse::MouseEvent) -> PageHandleEvent {
+        let cse::MouseEventKind::Down(cse::MouseButton::Left) = event.kind else {
+            return PageHandleEvent::None
         };
 
-        GamePage {
-            text_state: game_text,
-            input_text: String::from(""),
+        let position = (event.column, event.row).into();
+        let Some(&(_, button)) = self.button_areas.iter()
+            .find(|(area, _)| area.contains(position)) else {
+            return PageHandleEvent::None
+        };
+
+        self.press_button(dispatcher, button);
+        PageHandleEvent::Consume
+    }
 
-            has_error: false,
-            error_at_char: 0,
-            current_word: 0,
+    fn draw_buttons(&mut self, frame: &mut Frame, area: Rect) {
+        let constraints = ResultButton::ALL.iter()
+            .map(|button| Constraint::Length(button.label().len() as u16));
+        let areas = Layout::horizontal(constraints)
+            .spacing(2)
+            .split(area);
 
-            done: false,
-            started: false,
+        self.button_areas.clear();
 
-            start_time: None,
-            end_time: None,
+        for (&button, &button_area) in ResultButton::ALL.iter().zip(areas.iter()) {
+            frame.render_widget(Span::styled(button.label(), STYLE_BUTTON), button_area);
+            self.button_areas.push((button_area, button));
         }
     }
 
-    pub fn default() -> Self {
-        GamePage::new(DEFAULT_TEXT.to_string())
+    pub fn with_settings(settings: &Settings) -> Self {
+        GamePage::with_text(DEFAULT_TEXT.to_string(), settings)
     }
 
-    fn verify_word(&mut self) {
-        let current_word = &self.text_state.words[self.current_word];
-        let has_next = self.current_word + 1 != self.text_state.words.len();
+    pub fn with_text(text: String, settings: &Settings) -> Self {
+        let mut game = GamePage::new(text, settings.session_options());
+        game.layout = settings.layout.clone();
+        game.keyboard = settings.keyboard;
+        game.caret = settings.caret;
+        game.blink = settings.blink;
+        game.focus = settings.focus;
+        game.buttons = true;
+
+        game.ghost = settings.ghost.and_then(|target| game.load_ghost(target));
+        game.pace_wpm = settings.pace.and_then(|pace| match pace {
+            Pace::Wpm(wpm) => Some(wpm),
+            Pace::Average => History::load().ok()?.average_wpm(),
+        });
+        game
+    }
 
-        if has_next && self.input_text == format!("{current_word} ") {
-            self.current_word += 1;
-            self.input_text = String::from("");
-            self.has_error = false;
+    /// Drill built from the weakest keys, bigrams and words so far.
+    pub fn practice(settings: &Settings) -> Self {
+        let stats = PracticeStats::load().unwrap_or_default();
+        let text = stats.drill(&mut Rng::from_time(), PRACTICE_WORDS);
+
+        GamePage::with_text(text, settings)
+    }
+
+    pub fn mode(mode: GameMode, settings: &Settings) -> Self {
+        let text = drill::game_text(mode, &mut Rng::from_time());
+
+        let mut game = GamePage::with_text(text, settings);
+
+        if let GameMode::Timed(seconds) = mode {
+            game.time_limit = Some(Duration::from_secs(seconds));
+            game.replay.limit = game.time_limit;
+        }
+
+        game
+    }
+
+    pub fn lesson(index: usize, settings: &Settings) -> Self {
+        let text = Lesson::drill(index, &settings.layout, &mut Rng::from_time(), LESSON_WORDS);
+        let mut game = GamePage::with_text(text, settings);
+        game.lesson = Some(index);
+        game
+    }
+
+    fn load_ghost(&self, target: GhostTarget) -> Option<Ghost> {
+        let kind = match target {
+            GhostTarget::Best => "best",
+            GhostTarget::Last => "last",
+        };
+
+        let id = replay::text_id(&self.replay.text);
+        let path = storage::data_dir().join(format!("replays/{id}-{kind}.replay"));
+        let replay = Replay::load(&path).ok()?;
+
+        Some(Ghost::from_replay(kind, &replay))
+    }
+
+    fn save_run(&mut self) {
+        if !self.record {
+            return;
         }
 
-        if !has_next && &self.input_text == current_word {
-            self.current_word += 1;
-            self.input_text = String::from("");
-            self.has_error = false;
-            self.done = true;
-            self.end_time = Some(Instant::now());
+        let Some(results) = self.session.results() else {
+            return;
+        };
+
+        let record = RunRecord::now(
+            results.wpm,
+            results.accuracy,
+            results.words,
+            results.errors,
+            results.duration,
+        );
+
+        let saved = self.save_replays()
+            .and_then(|_| History::append(&record))
+            .and_then(|_| self.save_practice())
+            .and_then(|_| self.save_lesson(&record));
+
+        if let Err(err) = saved {
+            self.save_error = Some(format!("Could not save run: {err}"));
         }
+    }
 
-        let iter = self.input_text.chars()
-            .zip(current_word.chars())
-            .enumerate();
+    fn save_practice(&mut self) -> std::io::Result<()> {
+        let mut stats = PracticeStats::load()?;
+        stats.record_run(self.session.words(), &self.events);
+        stats.save()?;
 
-        self.has_error = false;
+        let advice = stats.advice();
+        self.advice = (!advice.is_empty()).then_some(advice);
 
-        for (index, (c1, c2)) in iter {
-            if c1 != c2 {
-                self.has_error = true;
-                self.error_at_char = index;
-                break;
-            }
+        Ok(())
+    }
+
+    fn save_lesson(&mut self, record: &RunRecord) -> std::io::Result<()> {
+        let Some(index) = self.lesson else {
+            return Ok(())
+        };
+
+        let mut progress = LessonProgress::load(&self.layout)?;
+        let score = LessonScore {
+            wpm: record.wpm,
+            accuracy: record.accuracy,
+        };
+        let passed = progress.record(index, score);
+        progress.save(&self.layout)?;
+
+        let lesson = &LESSONS[index];
+        self.lesson_status = Some(if !passed {
+            format!(
+                "Reach {:.0} WPM with {:.0}% accuracy to pass the lesson.",
+                lesson.target_wpm,
+                lesson.target_accuracy,
+            )
+        } else if index + 1 < LESSONS.len() {
+            "Lesson passed, the next one is unlocked.".to_string()
+        } else {
+            "Lesson passed, that was the last one.".to_string()
+        });
+
+        Ok(())
+    }
+
+    /// Save the run as the last one, both overall and for this text,
+    /// and as the best one for this text if it was the fastest.
+    fn save_replays(&self) -> std::io::Result<()> {
+        let id = replay::text_id(&self.replay.text);
+
+        self.replay.save(&storage::data_path("replays/last.replay")?)?;
+        self.replay.save(&storage::data_path(&format!("replays/{id}-last.replay"))?)?;
+
+        // Faster but sloppy runs don't count, wpm only counts correct words.
+        let wpm = self.session.results().map_or(0.0, |results| results.wpm);
+        let best_path = storage::data_path(&format!("replays/{id}-best.replay"))?;
+        let is_best = Replay::load(&best_path).ok()
+            .and_then(|best| best.results())
+            .is_none_or(|best| wpm > best.wpm);
+
+        if is_best {
+            self.replay.save(&best_path)?;
         }
+
+        Ok(())
     }
 
-    fn handle_key(&mut self, event_key: &cse::KeyEvent) -> PageHandleEvent {
-        if self.done {
+    /// End a timed run once its time is up.
+    pub fn check_time_limit(&mut self, now: Instant) {
+        let Some(limit) = self.time_limit else {
+            return
+        };
+
+        let elapsed = self.session.elapsed(now);
+
+        if !self.session.is_started() || self.session.is_done() || elapsed < limit {
+            return
+        }
+
+        // Ticks come late, the run ends right at the limit.
+        let events = self.session.stop(now - (elapsed - limit));
+
+        if self.record {
+            self.events.extend(events);
+        }
+
+        self.save_run();
+    }
+
+    pub fn handle_key(&mut self, event_key: &cse::KeyEvent, now: Instant) -> PageHandleEvent {
+        self.check_time_limit(now);
+
+        if self.session.is_done() {
             return PageHandleEvent::None
         }
 
-        if !self.started {
+        if !self.session.is_started() {
             if let cse::KeyCode::Enter = event_key.code {
-                self.started = true;
-                self.start_time = Some(Instant::now());
+                self.start(now);
             }
             return PageHandleEvent::None
         }
 
-        match event_key.code {
-            cse::KeyCode::Backspace => {
-                self.input_text.pop();
-                self.verify_word();
-                PageHandleEvent::Consume
-            },
-            cse::KeyCode::Char(c) => {
-                if event_key.modifiers.contains(cse::KeyModifiers::CONTROL) && c == 'w' {
-                    self.input_text = String::from("");
-                    self.verify_word();
-                    return PageHandleEvent::Consume
-                }
+        let Some(key) = session_key(event_key) else {
+            return PageHandleEvent::None
+        };
 
-                self.input_text.push(c);
-                self.verify_word();
-                PageHandleEvent::Consume
-            },
-            _ => {
-                PageHandleEvent::None
-            },
+        self.idle_ticks = 0;
+
+        self.replay.record(self.session.elapsed(now), event_key);
+        let events = self.session.input(key, now);
+        let finished = events.contains(&SessionEvent::Finished);
+
+        for event in &events {
+            if let SessionEvent::Keystroke { typed, correct: false, .. } = event {
+                self.wrong_key = Some((*typed, now));
+            }
+        }
+
+        if self.record {
+            self.events.extend(events);
+        }
+
+        if finished {
+            self.save_run();
         }
+
+        PageHandleEvent::Consume
     }
 
-    fn format_current_word<'a>(&'a self, word: &'a str) -> Vec<Span<'a>> {
-        if !self.has_error {
-            let split_min = self.input_text.len().min(word.len());
-            let (typed, left) = word.split_at(split_min);
-            let mut first: &str = left;
-            let mut rest: &str = "";
+    /// Labels for the modes this run is played in.
+    fn badges(&self) -> Vec<String> {
+        let mut badges = Vec::new();
 
-            if !left.is_empty() {
-                (first, rest) = left.split_at(1);
-            }
+        if let Some(index) = self.lesson {
+            badges.push(format!("lesson {}", index + 1));
+        }
+
+        let error_mode = self.session.options().error_mode;
+        if error_mode != ErrorMode::Normal {
+            badges.push(error_mode.name().to_string());
+        }
 
-            vec![
-                Span::styled(typed, STYLE_CORRECT_CHARS),
-                Span::styled(first, STYLE_CURSOR),
-                Span::styled(rest, STYLE_ACTIVE_WORD),
-                Span::from(" "),
-            ]
+        if let Some(ghost) = &self.ghost {
+            badges.push(format!("ghost {}", ghost.label));
+        }
+
+        if let Some(wpm) = self.pace_wpm {
+            badges.push(format!("pace {wpm:.0}"));
+        }
+
+        badges
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focus && !self.session.is_done()
+    }
+
+    /// Width limited area in the middle of the screen, just tall enough
+    /// for the text, the status line and the keyboard.
+    fn focus_area(&self, rect: Rect, text_lines: usize) -> Rect {
+        let keyboard = if self.keyboard { KEYBOARD_HEIGHT + 1 } else { 0 };
+        let height = text_lines as u16 + 1 + 2 + keyboard;
+
+        let [area] = Layout::horizontal([Constraint::Max(FOCUS_WIDTH)])
+            .flex(Flex::Center)
+            .areas(rect);
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        area
+    }
+
+    fn caret_visible(&self) -> bool {
+        !self.blink || (self.idle_ticks / BLINK_TICKS).is_multiple_of(2)
+    }
+
+    /// Spans of the word being typed and the column of the caret in it.
+    fn format_current_word<'a>(&'a self, word: &'a str) -> (Vec<Span<'a>>, usize) {
+        let session = &self.session;
+        let input = session.input_line();
+        let word_len = word.chars().count();
+        let typed_len = input.len();
+        let split_min = typed_len.min(word_len);
+        let error_at = if session.has_error() {
+            session.error_at_char().min(split_min)
         } else {
-            let after_error = self.input_text.len().min(word.len())
-                - self.error_at_char;
-            let (typed, badrest) = word.split_at(self.error_at_char);
-            let (bad, left) = badrest.split_at(after_error);
-            let mut first: &str = left;
-            let mut rest : &str = "";
-
-            if !left.is_empty() {
-                (first, rest) = left.split_at(1);
+            split_min
+        };
+
+        let (typed, left) = split_at_char(word, error_at);
+        let (bad, untyped) = split_at_char(left, split_min - error_at);
+        let (_, overflow) = split_at_char(input.as_str(), word_len);
+
+        let error = session.has_error();
+        let caret = self.caret_visible().then_some(self.caret);
+        let cursor_style = match caret {
+            Some(CaretStyle::Block) if error => Some(STYLE_BAD_CURSOR),
+            Some(CaretStyle::Block) => Some(STYLE_CURSOR),
+            Some(CaretStyle::Underline) if error => Some(STYLE_BAD_UNDERLINE_CURSOR),
+            Some(CaretStyle::Underline) => Some(STYLE_UNDERLINE_CURSOR),
+            _ => None,
+        };
+
+        // The bar keeps its column while blinking so the text stays put.
+        let bar = match caret {
+            Some(CaretStyle::Bar) if error => Span::styled(CARET_BAR, STYLE_BAD_BAR_CURSOR),
+            Some(CaretStyle::Bar) => Span::styled(CARET_BAR, STYLE_BAR_CURSOR),
+            _ if self.caret == CaretStyle::Bar => Span::from(" "),
+            _ => Span::default(),
+        };
+
+        // Anything typed past the end of the word comes after it, the
+        // separator last. The caret goes where the input cursor is.
+        let segments = [
+            (typed, STYLE_CORRECT_CHARS),
+            (bad, STYLE_WRONG_CHARS),
+            (untyped, STYLE_ACTIVE_WORD),
+            (overflow, STYLE_OVERFLOW_CHARS),
+            (" ", Style::new()),
+        ];
+
+        let mut spans = Vec::new();
+        let mut column = 0;
+        let mut bar = Some(bar);
+        let mut before_caret = input.cursor();
+
+        for (text, style) in segments {
+            let len = text.chars().count();
+
+            if bar.is_none() || before_caret >= len {
+                before_caret = before_caret.saturating_sub(len);
+                spans.push(Span::styled(text, style));
+                continue;
             }
 
-            vec![
-                Span::styled(typed, STYLE_CORRECT_CHARS),
-                Span::styled(bad, STYLE_WRONG_CHARS),
-                Span::styled(first, STYLE_BAD_CURSOR),
-                Span::styled(rest, STYLE_ACTIVE_WORD),
-                Span::from(" "),
-            ]
+            let (before, at) = split_at_char(text, before_caret);
+            let (under, after) = split_at_char(at, 1);
+            spans.push(Span::styled(before, style));
+
+            let bar = bar.take().unwrap();
+            column = spans.iter().map(Span::width).sum::<usize>() + bar.width();
+            spans.extend([
+                bar,
+                Span::styled(under, cursor_style.unwrap_or(style)),
+                Span::styled(after, style),
+            ]);
         }
+
+        (spans, column)
     }
 }
 
+// Ghost racing and pace caret
+impl GamePage {
+    /// Format a word with a marker on its first character.
+    fn format_marked_word<'a>(&self, index: usize, word: &'a str, marker: Style) -> Vec<Span<'a>> {
+        let style = if index > self.session.current_word() {
+            Style::new()
+        } else if self.session.is_word_correct(index) {
+            Style::new().fg(Color::Green)
+        } else {
+            STYLE_WRONG_WORD
+        };
+
+        let (first, rest) = split_at_char(word, 1);
+
+        vec![
+            Span::styled(first, marker),
+            Span::styled(rest, style),
+            Span::from(" "),
+        ]
+    }
+
+    /// Word the pace caret is on, running at a constant WPM.
+    fn pace_word(&self, now: Instant) -> Option<usize> {
+        let wpm = self.pace_wpm.filter(|_| self.session.is_started())?;
+        let minutes = self.session.elapsed(now).as_secs_f64() / 60.0;
+
+        Some((minutes * wpm) as usize)
+    }
+
+    fn pace_status(&self, now: Instant) -> Option<String> {
+        let wpm = self.pace_wpm?;
+
+        if self.session.is_done() {
+            // Timed runs stop before the end of the text.
+            let target = Duration::from_secs_f64(
+                self.session.current_word() as f64 / wpm * 60.0
+            );
+            let lead = ghost::lead_secs(self.session.elapsed(now), target);
+            let verdict = if lead >= 0.0 { "ahead of" } else { "behind" };
+
+            return Some(format!(
+                "Finished {:.1}s {verdict} the {wpm:.0} WPM pace", lead.abs()
+            ));
+        }
+
+        let words = self.session.current_word() as i64 - self.pace_word(now)? as i64;
+
+        Some(format!("Pace ({wpm:.0} WPM): {words:+} words"))
+    }
+
+    /// Lead over the ghost in words and seconds, or the final difference
+    /// once the run is done.
+    fn ghost_status(&self, now: Instant) -> Option<String> {
+        let ghost = self.ghost.as_ref()?;
+        let label = ghost.label;
+
+        let session = &self.session;
+
+        if session.is_done() {
+            let finish = ghost.finish()?;
+            let lead = ghost::lead_secs(session.elapsed(now), finish);
+            let verdict = if lead >= 0.0 { "ahead of" } else { "behind" };
+
+            return Some(format!(
+                "Finished {:.1}s {verdict} the {label} run", lead.abs()
+            ));
+        }
+
+        let words = session.current_word() as i64
+            - ghost.word_at(session.elapsed(now)) as i64;
+
+        let ours = session.word_times().last().copied().unwrap_or_default();
+        let secs = ghost.time_to(session.current_word())
+            .map_or(0.0, |theirs| ghost::lead_secs(ours, theirs));
+
+        Some(format!("Ghost ({label}): {words:+} words, {secs:+.1}s"))
+    }
+}
+
+/// Lay words out on lines of `width` columns. Returns the lines and the
+/// line of every word.
+fn wrap_words(words: Vec<Vec<Span<'_>>>, width: u16) -> (Vec<Line<'_>>, Vec<usize>) {
+    let width = width as usize;
+    let mut lines: Vec<Line> = Vec::new();
+    let mut word_lines = Vec::with_capacity(words.len());
+    let mut line_width = 0;
+
+    for spans in words {
+        let word_width: usize = spans.iter().map(Span::width).sum();
+
+        // The separator after a word may hang past the edge.
+        if lines.is_empty() || line_width > 0 && line_width + word_width - 1 > width {
+            lines.push(Line::default());
+            line_width = 0;
+        }
+
+        line_width += word_width;
+        word_lines.push(lines.len() - 1);
+        lines.last_mut().unwrap().spans.extend(spans);
+    }
+
+    (lines, word_lines)
+}
+
+/// Split a string at the given character (not byte) index.
+fn split_at_char(text: &str, index: usize) -> (&str, &str) {
+    let byte_index = text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(i, _)| i);
+
+    text.split_at(byte_index)
+}
+
 impl Page for GamePage {
     fn draw(&mut self, frame: &mut Frame, rect: Rect) {
-        let words: Vec<Span> = self.text_state.words.iter()
+        let now = Instant::now();
+        let session = &self.session;
+        let current_word = session.current_word();
+        let ghost_word = self.ghost.as_ref()
+            .filter(|_| session.is_started())
+            .map(|ghost| ghost.word_at(session.elapsed(now)));
+        let pace_word = self.pace_word(now);
+        let mut caret_column = 0;
+
+        let words: Vec<Vec<Span>> = session.words().iter()
             .enumerate()
-            .flat_map(|(index, word)| {
-                if index < self.current_word {
+            .map(|(index, word)| {
+                if index == current_word {
+                    let (spans, column) = self.format_current_word(word);
+                    caret_column = column;
+                    spans
+                } else if ghost_word == Some(index) {
+                    self.format_marked_word(index, word, STYLE_GHOST)
+                } else if pace_word == Some(index) {
+                    self.format_marked_word(index, word, STYLE_PACE)
+                } else if index < current_word && session.is_word_correct(index) {
                     vec![format!("{word} ").fg(Color::Green)]
-                } else if index == self.current_word {
-                    self.format_current_word(word)
+                } else if index < current_word {
+                    vec![
+                        Span::styled(word.as_str(), STYLE_WRONG_WORD),
+                        Span::from(" "),
+                    ]
                 } else {
                     vec![format!("{word} ").into()]
                 }
             })
             .collect();
 
-        let line = Line::from(words);
-        let text = Text::from(line);
-        let par = Paragraph::new(text)
-            .wrap(Wrap { trim: true });
+        let widths: Vec<usize> = words.iter()
+            .map(|spans| spans.iter().map(Span::width).sum())
+            .collect();
+        let focused = self.is_focused();
+        let width = if focused { rect.width.min(FOCUS_WIDTH) } else { rect.width };
+        let (lines, word_lines) = wrap_words(words, width);
+        let visible = lines.len().min(VIEWPORT_LINES);
+        let rect = if focused { self.focus_area(rect, visible) } else { rect };
+
+        // Scroll a line at a time, keeping the line before the current
+        // one in view until the end of the text.
+        let current_line = word_lines.get(current_word)
+            .or(word_lines.last())
+            .copied()
+            .unwrap_or(0);
+        let first = current_line.saturating_sub(1)
+            .min(lines.len() - visible);
 
-        let lines = par.line_count(rect.width) as u16 + 1;
+        let text = Text::from(lines[first..first + visible].to_vec());
+        let par = Paragraph::new(text);
 
         let [text_area, input_area] = Layout::vertical([
-            Constraint::Length(lines),
-            Constraint::Length(2),
+            Constraint::Length(visible as u16 + 1),
+            Constraint::Min(2),
         ]).areas::<2>(rect);
 
-
         frame.render_widget(par, text_area);
 
-        let mut block = Block::new().borders(Borders::TOP);
-
-        if !self.done {
-            let text = Text::from(Line::from(vec![
-                Span::styled("> ", if self.has_error {
-                        STYLE_INPUT_PREFIX_ERROR
-                    } else {
-                        STYLE_INPUT_PREFIX
-                    }),
-                Span::styled(
-                    self.input_text.as_str(),
-                    STYLE_INPUT
-                )
-            ]));
-
-            if !self.started {
+        if self.caret == CaretStyle::Terminal && !session.is_done() && self.caret_visible() {
+            let line = word_lines[current_word];
+            let column = (0..current_word)
+                .filter(|&index| word_lines[index] == line)
+                .map(|index| widths[index])
+                .sum::<usize>() + caret_column;
+
+            let x = (text_area.x + column as u16).min(text_area.right().saturating_sub(1));
+            let y = text_area.y + (line - first) as u16;
+            frame.set_cursor_position((x, y));
+        }
+
+        let mut block = if focused {
+            Block::new()
+        } else {
+            Block::new().borders(Borders::TOP)
+        };
+
+        if !session.is_done() {
+            let (before, cursor, after) = session.input_line().split_at_cursor();
+            let cursor = if cursor.is_empty() { " " } else { cursor };
+
+            // In focus mode the text itself shows what was typed.
+            let text = if focused {
+                Text::default()
+            } else {
+                Text::from(Line::from(vec![
+                    Span::styled("> ", if session.has_error() {
+                            STYLE_INPUT_PREFIX_ERROR
+                        } else {
+                            STYLE_INPUT_PREFIX
+                        }),
+                    Span::styled(before, STYLE_INPUT),
+                    Span::styled(cursor, STYLE_INPUT_CURSOR),
+                    Span::styled(after, STYLE_INPUT),
+                ]))
+            };
+
+            if !session.is_started() {
                 block = block.title("Press Enter to start.");
+            } else {
+                let status = [self.ghost_status(now), self.pace_status(now)]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join("  ");
+
+                if !status.is_empty() {
+                    block = block.title(Line::from(status).right_aligned());
+                }
             }
 
             let input = Paragraph::new(text)
                 .block(block);
 
+            let [input_area, keyboard_area] = Layout::vertical([
+                Constraint::Length(2),
+                Constraint::Length(if self.keyboard { KEYBOARD_HEIGHT } else { 0 }),
+            ]).spacing(1).areas::<2>(input_area);
+
             frame.render_widget(input, input_area);
+
+            if self.keyboard {
+                let wrong_key = self.wrong_key
+                    .filter(|(_, at)| now.duration_since(*at) < WRONG_KEY_FLASH)
+                    .map(|(key, _)| key);
+                let keyboard = Keyboard::new(&self.layout)
+                    .next(session.next_char())
+                    .wrong(wrong_key);
+
+                frame.render_widget(keyboard, keyboard_area);
+            }
         } else {
-            let duration = self.end_time.unwrap()
-                .duration_since(self.start_time.unwrap());
-            let words = self.text_state.words.len();
-            let minutes = duration.as_secs_f64() / 60.0;
-            let words_per_minute = (words as f64 / minutes) as u32;
+            let words_per_minute = session.wpm(now) as u32;
+            let accuracy = session.accuracy();
+            let errors = session.errors();
+
+            let mut summary = format!(
+                "WPM: {words_per_minute}  Accuracy: {accuracy:.1}%  Errors: {errors}"
+            );
+
+            if session.options().error_mode == ErrorMode::Free {
+                let wrong = session.wrong_word_count();
+                summary.push_str(&format!("  Wrong words: {wrong}"));
+            }
+
+            let mut lines = vec![Line::from(summary)];
+
+            if let Some(status) = self.ghost_status(now) {
+                lines.push(Line::from(status));
+            }
+
+            if let Some(status) = self.pace_status(now) {
+                lines.push(Line::from(status));
+            }
+
+            if let Some(status) = &self.lesson_status {
+                lines.push(Line::from(status.as_str()));
+            }
+
+            if let Some(advice) = &self.advice {
+                lines.push(Line::from(format!("Practice next: {advice}")));
+            }
+
+            if let Some(err) = &self.save_error {
+                lines.push(Line::styled(err.as_str(), STYLE_WRONG_CHARS));
+            }
+
+            let wrong_words = (0..session.typed_words().len())
+                .filter(|&index| !session.is_word_correct(index));
 
-            let stats = Paragraph::new(format!("WPM: {words_per_minute}"))
+            for index in wrong_words {
+                let expected = &session.words()[index];
+                let typed = &session.typed_words()[index];
+
+                lines.push(Line::from(vec![
+                    Span::styled(expected.as_str(), STYLE_CORRECT_CHARS),
+                    Span::from(" <- "),
+                    Span::styled(typed.as_str(), STYLE_WRONG_CHARS),
+                ]));
+            }
+
+            let stats = Paragraph::new(Text::from(lines))
                 .block(block);
 
-            frame.render_widget(stats, input_area);
+            if self.buttons {
+                let [stats_area, buttons_area] = Layout::vertical([
+                    Constraint::Min(1),
+                    Constraint::Length(1),
+                ]).areas::<2>(input_area);
+
+                frame.render_widget(stats, stats_area);
+                self.draw_buttons(frame, buttons_area);
+            } else {
+                frame.render_widget(stats, input_area);
+            }
         }
     }
 
-    fn handle_event(&mut self, _: AppEventDispatcher, event: &AppEvent) -> PageHandleEvent {
+    fn handle_event(&mut self, dispatcher: AppEventDispatcher, event: &AppEvent) -> PageHandleEvent {
         match event {
+            AppEvent::Crossterm(cse::Event::Mouse(mouse_event)) if self.session.is_done() => {
+                self.handle_mouse(dispatcher, mouse_event)
+            },
+            AppEvent::Crossterm(cse::Event::Key(key_event))
+                if self.buttons
+                    && self.session.is_done()
+                    && key_event.kind == cse::KeyEventKind::Press
+                    && key_event.code == cse::KeyCode::Enter => {
+                self.press_button(dispatcher, ResultButton::Again);
+                PageHandleEvent::Consume
+            },
             AppEvent::Crossterm(cse) => {
                 if let cse::Event::Key(key_event) = cse && key_event.kind == cse::KeyEventKind::Press {
-                    self.handle_key(key_event)
+                    self.handle_key(key_event, Instant::now())
                 } else {
                     PageHandleEvent::None
                 }
             },
+            AppEvent::Tick => {
+                self.idle_ticks = self.idle_ticks.wrapping_add(1);
+                self.check_time_limit(Instant::now());
+                PageHandleEvent::None
+            },
             _ => {
                 PageHandleEvent::None
             },
         }
     }
 
+    fn is_busy(&self) -> bool {
+        self.session.is_started() && !self.session.is_done()
+    }
+
+    fn fullscreen(&self) -> bool {
+        self.is_focused()
+    }
+
+    fn header(&self) -> Line<'_> {
+        let mut widgets: Vec<Span> = self.badges().into_iter()
+            .map(|badge| Span::styled(format!(" {badge} "), STYLE_BADGE))
+            .collect();
+
+        if self.session.is_started() {
+            let elapsed = self.session.elapsed(Instant::now());
+            // Timed runs count down.
+            let shown = match self.time_limit {
+                Some(limit) => limit.saturating_sub(elapsed).as_secs_f64().ceil() as u64,
+                None => elapsed.as_secs(),
+            };
+
+            widgets.push(Span::from(format!("Time {}:{:02}", shown / 60, shown % 60)));
+        }
+
+        let mut spans = Vec::new();
+        for widget in widgets {
+            if !spans.is_empty() {
+                spans.push(Span::from(" "));
+            }
+            spans.push(widget);
+        }
+
+        Line::from(spans)
+    }
+
+    fn footer_hints(&self) -> Vec<(&'static str, &'static str)> {
+        if !self.session.is_started() {
+            vec![("Enter", "start")]
+        } else if self.buttons && self.session.is_done() {
+            vec![("Enter", "play again")]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn terminal_title(&self) -> Option<String> {
+        let session = &self.session;
+
+        if !session.is_started() || session.is_done() {
+            return None
+        }
+
+        Some(format!("{APP_NAME} - {:.0} WPM", session.wpm(Instant::now())))
+    }
+
     fn page_title(&self) -> &str {
         "Game"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stamba::engine::Key;
+
+    fn game(text: &str) -> (GamePage, Instant) {
+        let now = Instant::now();
+        let mut game = GamePage::new(text.to_string(), SessionOptions::default());
+        game.record = false;
+        game.start(now);
+
+        (game, now)
+    }
+
+    fn type_str(game: &mut GamePage, text: &str, now: Instant) {
+        for c in text.chars() {
+            game.session.input(Key::Char(c), now);
+        }
+    }
+
+    fn spans(game: &GamePage, word: &str) -> Vec<(String, Style)> {
+        game.format_current_word(word).0.into_iter()
+            .filter(|span| !span.content.is_empty())
+            .map(|span| (span.content.to_string(), span.style))
+            .collect()
+    }
+
+    #[test]
+    fn untouched_word() {
+        let (game, _) = game("hello");
+
+        assert_eq!(spans(&game, "hello"), [
+            ("h".to_string(), STYLE_CURSOR),
+            ("ello".to_string(), STYLE_ACTIVE_WORD),
+            (" ".to_string(), Style::new()),
+        ]);
+    }
+
+    #[test]
+    fn error_at_end_of_word() {
+        let (mut game, now) = game("ab cd");
+        type_str(&mut game, "ax", now);
+
+        assert_eq!(spans(&game, "ab"), [
+            ("a".to_string(), STYLE_CORRECT_CHARS),
+            ("b".to_string(), STYLE_WRONG_CHARS),
+            (" ".to_string(), STYLE_BAD_CURSOR),
+        ]);
+    }
+
+    #[test]
+    fn overflow_after_word() {
+        let (mut game, now) = game("ab cd");
+        type_str(&mut game, "abxy", now);
+
+        assert_eq!(spans(&game, "ab"), [
+            ("ab".to_string(), STYLE_CORRECT_CHARS),
+            ("xy".to_string(), STYLE_OVERFLOW_CHARS),
+            (" ".to_string(), STYLE_BAD_CURSOR),
+        ]);
+    }
+
+    #[test]
+    fn non_ascii_word() {
+        let (mut game, now) = game("çàé");
+        type_str(&mut game, "çx", now);
+
+        assert_eq!(spans(&game, "çàé"), [
+            ("ç".to_string(), STYLE_CORRECT_CHARS),
+            ("à".to_string(), STYLE_WRONG_CHARS),
+            ("é".to_string(), STYLE_BAD_CURSOR),
+            (" ".to_string(), Style::new()),
+        ]);
+    }
+
+    #[test]
+    fn wraps_words_to_width() {
+        let words = ["ab", "cd", "efgh", "i"].iter()
+            .map(|word| vec![Span::from(format!("{word} "))])
+            .collect();
+        let (lines, word_lines) = wrap_words(words, 5);
+
+        assert_eq!(word_lines, [0, 0, 1, 2]);
+        assert_eq!(lines[0].to_string(), "ab cd ");
+        assert_eq!(lines[1].to_string(), "efgh ");
+    }
+
+    #[test]
+    fn viewport_follows_current_word() {
+        let text = (0..40).map(|i| format!("w{i:02}")).collect::<Vec<_>>().join(" ");
+        let (mut game, now) = game(&text);
+        let typed = (0..20).map(|i| format!("w{i:02} ")).collect::<String>();
+        type_str(&mut game, &typed, now);
+
+        let backend = ratatui::backend::TestBackend::new(16, 8);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|frame| game.draw(frame, frame.area())).unwrap();
+
+        let screen: String = terminal.backend().buffer().content().iter()
+            .map(|cell| cell.symbol())
+            .collect();
+
+        // Four words a line, the current one is on the second line.
+        assert!(screen.starts_with("w16 w17 w18 w19 w20 w21 w22 w23 w24 w25 w26 w27"), "{screen}");
+    }
+
+    #[test]
+    fn empty_text_draws() {
+        let (mut game, _) = game("");
+        assert!(game.session.is_done());
+
+        let backend = ratatui::backend::TestBackend::new(20, 5);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|frame| game.draw(frame, frame.area())).unwrap();
+    }
+
+    #[test]
+    fn header_shows_modes_and_time() {
+        let options = SessionOptions { error_mode: ErrorMode::Free, ..Default::default() };
+        let mut game = GamePage::new("hello".to_string(), options);
+        game.lesson = Some(2);
+
+        assert_eq!(game.header().to_string(), " lesson 3   free ");
+        assert_eq!(game.footer_hints(), [("Enter", "start")]);
+
+        game.start(Instant::now());
+        assert_eq!(game.header().to_string(), " lesson 3   free  Time 0:00");
+        assert!(game.footer_hints().is_empty());
+    }
+
+    #[test]
+    fn timed_run_stops_at_limit() {
+        let (mut game, now) = game("ab cd ef gh");
+        game.time_limit = Some(Duration::from_secs(30));
+        type_str(&mut game, "ab cd e", now);
+
+        game.check_time_limit(now + Duration::from_secs(29));
+        assert!(!game.session.is_done());
+
+        game.check_time_limit(now + Duration::from_secs(31));
+        assert!(game.session.is_done());
+        assert_eq!(game.session.elapsed(now), Duration::from_secs(30));
+        assert_eq!(game.session.results().unwrap().words, 2);
+    }
+
+    #[test]
+    fn bar_caret_blinks_in_place() {
+        let (mut game, now) = game("ab cd");
+        game.caret = CaretStyle::Bar;
+        game.blink = true;
+        type_str(&mut game, "ax", now);
+
+        let (spans, column) = game.format_current_word("ab");
+        assert_eq!(column, 3);
+        assert!(spans.contains(&Span::styled(CARET_BAR, STYLE_BAD_BAR_CURSOR)));
+
+        game.idle_ticks = BLINK_TICKS;
+        let (spans, column) = game.format_current_word("ab");
+        assert_eq!(column, 3);
+        assert!(spans.contains(&Span::from(" ")) && !spans.iter().any(|span| span.content == CARET_BAR));
+    }
+
+    #[test]
+    fn caret_follows_the_input_cursor() {
+        let (mut game, now) = game("hello world");
+        type_str(&mut game, "hxl", now);
+        game.handle_key(&cse::KeyEvent::from(cse::KeyCode::Left), now);
+        game.handle_key(&cse::KeyEvent::from(cse::KeyCode::Left), now);
+
+        assert_eq!(game.format_current_word("hello").1, 1);
+        assert_eq!(spans(&game, "hello"), [
+            ("h".to_string(), STYLE_CORRECT_CHARS),
+            ("e".to_string(), STYLE_BAD_CURSOR),
+            ("l".to_string(), STYLE_WRONG_CHARS),
+            ("lo".to_string(), STYLE_ACTIVE_WORD),
+            (" ".to_string(), Style::new()),
+        ]);
+
+        game.handle_key(&cse::KeyEvent::from(cse::KeyCode::End), now);
+        assert_eq!(game.format_current_word("hello").1, 3);
+    }
+
+    #[test]
+    fn terminal_caret_sets_cursor() {
+        let (mut game, now) = game("one two three four");
+        game.caret = CaretStyle::Terminal;
+        type_str(&mut game, "one tw", now);
+
+        assert_eq!(spans(&game, "two"), [
+            ("tw".to_string(), STYLE_CORRECT_CHARS),
+            ("o".to_string(), STYLE_ACTIVE_WORD),
+            (" ".to_string(), Style::new()),
+        ]);
+
+        let backend = ratatui::backend::TestBackend::new(10, 8);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(|frame| game.draw(frame, Rect::new(1, 1, 9, 7))).unwrap();
+
+        // "one two " fits on the first line, the caret is on the "o".
+        assert_eq!(terminal.get_cursor_position().unwrap(), (7, 1).into());
+    }
+
+    #[test]
+    fn best_replay_is_the_fastest_correct_run() {
+        let dir = std::env::temp_dir().join(format!("stamba-best-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        storage::set_data_dir(dir.clone());
+
+        let play = |typed: &str, step: Duration| {
+            let options = SessionOptions { error_mode: ErrorMode::Free, ..Default::default() };
+            let mut game = GamePage::new("ab cd".to_string(), options);
+            let now = Instant::now();
+            game.start(now);
+
+            for (index, c) in typed.chars().enumerate() {
+                game.handle_key(&cse::KeyEvent::from(cse::KeyCode::Char(c)), now + step * index as u32);
+            }
+            assert!(game.session.is_done());
+        };
+
+        play("ab cd", Duration::from_millis(500));
+        // Quicker, but every word is wrong.
+        play("ax cx ", Duration::from_millis(50));
+
+        let id = replay::text_id("ab cd");
+        let best = Replay::load(&dir.join(format!("replays/{id}-best.replay"))).unwrap();
+        assert_eq!(best.results().unwrap().words, 2);
+        assert!(best.results().unwrap().wrong_words.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn pace_verdict_counts_completed_words() {
+        let (mut game, now) = game("a b c d e f g h");
+        game.pace_wpm = Some(60.0);
+        type_str(&mut game, "a b ", now);
+        game.session.stop(now + Duration::from_secs(3));
+
+        let status = game.pace_status(now + Duration::from_secs(3));
+        assert_eq!(status.as_deref(), Some("Finished 1.0s behind the 60 WPM pace"));
+    }
+}
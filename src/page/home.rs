@@ -1,83 +1,235 @@
+use std::time::Duration;
+
 use crossterm::event as cse;
 
 use ratatui::Frame;
-use ratatui::layout::{Rect, Layout, Constraint};
-use ratatui::style::{Style, Modifier};
-use ratatui::widgets::{List, ListState, ListItem};
+use ratatui::layout::{Rect, Layout, Constraint, Flex, Position};
+use ratatui::style::{Style, Modifier, Color};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListState, ListItem, Padding, Paragraph, Wrap};
 
+use crate::dialog::Dialog;
 use crate::events::{AppEventDispatcher, AppAction, AppEvent};
-use super::{LoadablePage, Page, PageHandleEvent};
+use crate::history::{self, History};
+use crate::settings::GameMode;
+use crate::storage;
+use super::menu::{EntryKind, Menu, MenuEntry};
+use super::{list_item_at, LoadablePage, Page, PageHandleEvent};
 
 const SELECTED_STYLE: Style = Style::new()
     .add_modifier(Modifier::BOLD)
     .add_modifier(Modifier::UNDERLINED);
 
-#[derive(Debug)]
-pub struct MenuItem {
-    name: String,
-    action: fn() -> AppEvent,
+const STYLE_DESCRIPTION: Style = Style::new()
+    .fg(Color::Gray);
+
+const STYLE_HEADING: Style = Style::new()
+    .add_modifier(Modifier::BOLD);
+
+const STYLE_MODE: Style = Style::new()
+    .fg(Color::Gray);
+
+const STYLE_MODE_SELECTED: Style = Style::new()
+    .fg(Color::Black)
+    .bg(Color::Yellow)
+    .add_modifier(Modifier::BOLD);
+
+/// Selected mode while keys go to the menu.
+const STYLE_MODE_PICKED: Style = Style::new()
+    .fg(Color::Yellow)
+    .add_modifier(Modifier::BOLD);
+
+const STYLE_LENGTH: Style = Style::new()
+    .fg(Color::Yellow);
+
+/// Width of the last run and today panel, border included.
+const STATS_WIDTH: u16 = 26;
+
+/// Word counts offered for a custom game, the last item asks for one.
+const LENGTHS: [usize; 3] = [200, 500, 1000];
+
+/// Longest custom game, the whole text is laid out on every frame.
+const MAX_CUSTOM_WORDS: usize = 1000;
+
+fn custom_length_dialog() -> Dialog {
+    let items = LENGTHS.iter()
+        .map(|count| format!("{count} words"))
+        .chain(["Other...".to_string()])
+        .collect();
+
+    Dialog::pick("Length", items, |index| match LENGTHS.get(index) {
+        Some(&count) => AppAction::GoTo(LoadablePage::Game(GameMode::Words(count))),
+        None => AppAction::Dialog(Dialog::number("Length", &format!("How many words? Up to {MAX_CUSTOM_WORDS}."), MAX_CUSTOM_WORDS, |count| {
+            AppAction::GoTo(LoadablePage::Game(GameMode::Words(count)))
+        })),
+    })
+}
+
+fn last_replay_exists() -> bool {
+    storage::data_dir().join("replays/last.replay").exists()
 }
 
+fn stats_menu() -> Menu {
+    Menu::new("Stats")
+        .entry(MenuEntry::action("Finger Stats", AppAction::GoTo(LoadablePage::FingerStats))
+            .shortcut('f')
+            .description("Speed and errors of every finger."))
+        .entry(MenuEntry::run("Replay Last Run", |_| {
+                let path = storage::data_dir().join("replays/last.replay");
+                AppAction::GoTo(LoadablePage::Replay(path))
+            })
+            .shortcut('r')
+            .available(last_replay_exists)
+            .description("Watch your last run again."))
+}
+
+fn main_menu() -> Menu {
+    Menu::new("Main Page")
+        .entry(MenuEntry::action("Quick Game", AppAction::GoTo(LoadablePage::GamePage))
+            .shortcut('g')
+            .description("Type the example text."))
+        .entry(MenuEntry::action("Lessons", AppAction::GoTo(LoadablePage::Lessons))
+            .shortcut('l')
+            .description("Learn the layout a few keys at a time."))
+        .entry(MenuEntry::action("Smart Practice", AppAction::GoTo(LoadablePage::Practice))
+            .shortcut('s')
+            .description("Drill built from your weakest keys and words."))
+        .entry(MenuEntry::submenu("Stats", stats_menu())
+            .shortcut('v')
+            .description("Finger stats and replays."))
+        .entry(MenuEntry::action("Quit", AppAction::Exit)
+            .shortcut('x'))
+}
+
+/// Menu with submenus, opened entries are kept in `path`.
 #[derive(Debug)]
 pub struct MenuPage {
-    pub menu_items: Vec<MenuItem>,
-    pub menu_max_len: u16,
-    pub state: ListState,
+    root: Menu,
+    /// Entry indexes leading from the root to the open submenu.
+    path: Vec<usize>,
+    /// Selection of every open menu, the last one is shown.
+    states: Vec<ListState>,
+    /// Where the list was drawn, for clicks.
+    list_area: Rect,
+    /// Highlight the selected entry, off while keys go elsewhere.
+    focused: bool,
 }
 
 impl MenuPage {
-    pub fn new(menu_items: Vec<MenuItem>) -> Self {
-        assert!(!menu_items.is_empty());
+    pub fn new(root: Menu) -> Self {
+        assert!(!root.entries.is_empty());
 
-        let mut state = ListState::default();
-        state.select(Some(0));
+        MenuPage {
+            root,
+            path: Vec::new(),
+            states: vec![ListState::default().with_selected(Some(0))],
+            list_area: Rect::default(),
+            focused: true,
+        }
+    }
 
-        let menu_max_len = menu_items.iter()
-            .map(|item| item.name.len())
-            .max()
-            .unwrap();
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
 
-        MenuPage {
-            menu_items,
-            menu_max_len: menu_max_len as u16,
-            state
+    pub fn in_submenu(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    fn menu(&self) -> &Menu {
+        self.path.iter().fold(&self.root, |menu, &index| {
+            match &menu.entries[index].kind {
+                EntryKind::Submenu(submenu) => submenu,
+                _ => unreachable!("menu path goes through submenus only"),
+            }
+        })
+    }
+
+    fn menu_mut(&mut self) -> &mut Menu {
+        self.path.iter().fold(&mut self.root, |menu, &index| {
+            match &mut menu.entries[index].kind {
+                EntryKind::Submenu(submenu) => submenu,
+                _ => unreachable!("menu path goes through submenus only"),
+            }
+        })
+    }
+
+    fn state(&mut self) -> &mut ListState {
+        self.states.last_mut().unwrap()
+    }
+
+    fn selected(&self) -> usize {
+        self.states.last().and_then(ListState::selected).unwrap_or(0)
+    }
+
+    fn select(&mut self, index: usize) {
+        let last = self.menu().entries.len() - 1;
+        self.state().select(Some(index.min(last)));
+    }
+
+    /// Close the open submenu, returns false on the main menu.
+    fn close_submenu(&mut self) -> bool {
+        if self.path.is_empty() {
+            return false;
         }
+
+        self.path.pop();
+        self.states.pop();
+        true
     }
 }
 
 impl Default for MenuPage {
     fn default() -> Self {
-        let items = vec![
-            MenuItem {
-                name: "Quick Game".to_string(),
-                action: || {
-                    AppEvent::App(AppAction::GoTo(LoadablePage::GamePage))
-                }
-            },
-            MenuItem {
-                name: "Quit".to_string(),
-                action: || {
-                    AppEvent::App(AppAction::Exit)
-                }
-            },
-        ];
-
-        MenuPage::new(items)
+        MenuPage::new(main_menu())
     }
 }
 
 impl Page for MenuPage {
+    fn on_resume(&mut self) {
+        self.root.refresh();
+    }
+
     fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let menu = self.menu();
+        let items: Vec<ListItem> = menu.entries.iter()
+            .map(|entry| ListItem::new(entry.line()))
+            .collect();
+        let height = items.len() as u16;
+        let description = menu.entries.get(self.selected())
+            .map(|entry| entry.description.clone())
+            .unwrap_or_default();
+
         let [_, content, _] = Layout::horizontal([
             Constraint::Min(1),
-            Constraint::Length(self.menu_max_len + 3),
+            Constraint::Length(menu.width() + 3),
             Constraint::Min(1),
         ]).areas::<3>(rect);
 
-        let list = List::new(&self.menu_items)
-            .highlight_style(SELECTED_STYLE);
+        let [list_area, description_area] = Layout::vertical([
+            Constraint::Max(height),
+            Constraint::Fill(1),
+        ]).spacing(1).areas::<2>(rect);
+
+        let list_area = list_area.intersection(content);
+
+        let highlight = if self.focused { SELECTED_STYLE } else { Style::new() };
+        let list = List::new(items)
+            .highlight_style(highlight);
+
+        // The list borrows the menu, so it renders with a copy of the
+        // selection that goes back afterwards for the scroll offset.
+        let mut state = self.states.last().cloned().unwrap_or_default();
+        frame.render_stateful_widget(list, list_area, &mut state);
+        *self.state() = state;
+        self.list_area = list_area;
 
-        frame.render_stateful_widget(list, content, &mut self.state);
+        let description = Paragraph::new(description)
+            .style(STYLE_DESCRIPTION)
+            .wrap(Wrap { trim: true })
+            .centered();
+
+        frame.render_widget(description, description_area);
     }
 
     fn handle_event(&mut self, app_events: AppEventDispatcher, event: &AppEvent) -> PageHandleEvent {
@@ -85,26 +237,51 @@ impl Page for MenuPage {
             AppEvent::Crossterm(cse) => {
                 self.handle_cse_event(app_events, cse)
             },
-            AppEvent::App(_) => {
+            AppEvent::App(_) | AppEvent::Race(_) | AppEvent::Tick => {
                 PageHandleEvent::None
             }
         }
     }
-    // fn action(&mut self) -> AppAction {
-    //     std::mem::replace(&mut self.action_todo, AppAction::None)
-    // }
+
+    fn footer_hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("j/k", "move"), ("Enter", "select")]
+    }
 
     fn page_title(&self) -> &str {
-        "Main Page"
+        &self.menu().title
     }
 }
 
 impl MenuPage {
     fn handle_page_action(&mut self, event_dispatcher: AppEventDispatcher) {
-        let selected = self.state.selected().unwrap();
-        let item = &self.menu_items[selected];
+        let selected = self.selected();
+        let menu = self.menu();
+        let entry = &menu.entries[selected];
+
+        if !entry.enabled {
+            return;
+        }
 
-        event_dispatcher.dispatch((item.action)())
+        match &entry.kind {
+            EntryKind::Action(action) => {
+                event_dispatcher.dispatch(AppEvent::App(action.clone()));
+            },
+            EntryKind::Run(run) => {
+                event_dispatcher.dispatch(AppEvent::App(run(menu)));
+            },
+            EntryKind::Submenu(_) => {
+                self.path.push(selected);
+                self.states.push(ListState::default().with_selected(Some(0)));
+            },
+            EntryKind::Choice { .. } => {
+                self.menu_mut().entries[selected].step(1);
+            },
+        }
+    }
+
+    fn step_choice(&mut self, delta: isize) {
+        let selected = self.selected();
+        self.menu_mut().entries[selected].step(delta);
     }
 
     fn handle_cse_event(&mut self, event_dispatcher: AppEventDispatcher, event: &cse::Event) -> PageHandleEvent {
@@ -112,33 +289,373 @@ impl MenuPage {
             cse::Event::Key(key_event) if key_event.kind == cse::KeyEventKind::Press => {
                 return self.handle_cse_key_event(event_dispatcher, key_event);
             },
+            cse::Event::Mouse(mouse_event) => {
+                return self.handle_mouse_event(event_dispatcher, mouse_event);
+            },
             _ => {}
         }
 
         PageHandleEvent::None
     }
 
+    fn handle_mouse_event(&mut self, event_dispatcher: AppEventDispatcher, event: &cse::MouseEvent) -> PageHandleEvent {
+        match event.kind {
+            cse::MouseEventKind::Down(cse::MouseButton::Left) => {
+                let len = self.menu().entries.len();
+                let state = self.states.last().unwrap();
+                let Some(index) = list_item_at(self.list_area, state, len, event.column, event.row) else {
+                    return PageHandleEvent::None
+                };
+
+                self.select(index);
+                self.handle_page_action(event_dispatcher);
+            },
+            cse::MouseEventKind::ScrollUp => {
+                self.select(self.selected().saturating_sub(1));
+            },
+            cse::MouseEventKind::ScrollDown => {
+                self.select(self.selected() + 1);
+            },
+            _ => return PageHandleEvent::None,
+        }
+
+        PageHandleEvent::Consume
+    }
 
     fn handle_cse_key_event(&mut self, event_dispatcher: AppEventDispatcher, event: &cse::KeyEvent) -> PageHandleEvent {
         match event.code {
             cse::KeyCode::Char('k') | cse::KeyCode::Up => {
-                self.state.select_previous();
+                self.select(self.selected().saturating_sub(1));
             },
             cse::KeyCode::Char('j') | cse::KeyCode::Down => {
-                self.state.select_next();
+                self.select(self.selected() + 1);
+            },
+            cse::KeyCode::Left => {
+                self.step_choice(-1);
+            },
+            cse::KeyCode::Right => {
+                self.step_choice(1);
             },
-            cse::KeyCode::Enter => {
-                self.handle_page_action(event_dispatcher)
+            cse::KeyCode::Enter | cse::KeyCode::Char(' ') => {
+                self.handle_page_action(event_dispatcher);
+            },
+            cse::KeyCode::Esc | cse::KeyCode::Backspace => {
+                if !self.close_submenu() {
+                    return PageHandleEvent::None
+                }
+            },
+            cse::KeyCode::Char(c) => {
+                let shortcut = self.menu().entries.iter()
+                    .position(|entry| entry.shortcut == Some(c));
+
+                let Some(index) = shortcut else {
+                    return PageHandleEvent::None
+                };
+
+                self.select(index);
+                self.handle_page_action(event_dispatcher);
             },
-            _ => {},
+            _ => return PageHandleEvent::None,
         }
 
-        PageHandleEvent::None
+        PageHandleEvent::Consume
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Time,
+    Words,
+    Quote,
+    Code,
+    Custom,
+}
+
+const MODES: [Mode; 5] = [Mode::Time, Mode::Words, Mode::Quote, Mode::Code, Mode::Custom];
+
+impl Mode {
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Time => "time",
+            Mode::Words => "words",
+            Mode::Quote => "quote",
+            Mode::Code => "code",
+            Mode::Custom => "custom",
+        }
+    }
+
+    /// Entry of the length menu this mode reads its length from.
+    fn length_entry(self) -> Option<&'static str> {
+        match self {
+            Mode::Time => Some("Time"),
+            Mode::Words => Some("Words"),
+            Mode::Quote | Mode::Code | Mode::Custom => None,
+        }
+    }
+}
+
+/// Lengths for the modes that have one, never shown as a list.
+fn lengths() -> Menu {
+    Menu::new("Length")
+        .entry(MenuEntry::choice("Time", &[15, 30, 60, 120], "s", 1))
+        .entry(MenuEntry::choice("Words", &[10, 25, 50, 100], " words", 1))
+}
+
+/// Main screen, Enter starts a game of the mode and length picked on top.
+#[derive(Debug)]
+pub struct HomePage {
+    mode: usize,
+    lengths: Menu,
+    menu: MenuPage,
+    /// Keys move through the menu instead of the mode bar.
+    menu_focused: bool,
+    history: History,
+    /// Where each mode of the bar was drawn, for clicks.
+    mode_areas: Vec<Rect>,
+    length_area: Rect,
+}
+
+impl HomePage {
+    pub fn new() -> Self {
+        HomePage {
+            mode: 0,
+            lengths: lengths(),
+            menu: MenuPage::default(),
+            menu_focused: false,
+            history: History::load().unwrap_or_default(),
+            mode_areas: Vec::new(),
+            length_area: Rect::default(),
+        }
+    }
+
+    /// The menu keeps the keys while one of its submenus is open.
+    fn is_menu_focused(&self) -> bool {
+        self.menu_focused || self.menu.in_submenu()
+    }
+
+    fn mode(&self) -> Mode {
+        MODES[self.mode]
+    }
+
+    fn step_mode(&mut self, delta: isize) {
+        self.mode = (self.mode as isize + delta).rem_euclid(MODES.len() as isize) as usize;
+    }
+
+    fn step_length(&mut self, delta: isize) {
+        let Some(name) = self.mode().length_entry() else {
+            return
+        };
+
+        if let Some(entry) = self.lengths.entries.iter_mut().find(|entry| entry.name == name) {
+            entry.step(delta);
+        }
+    }
+
+    fn length_text(&self) -> String {
+        match self.mode() {
+            Mode::Time | Mode::Words => {
+                let entry = self.mode().length_entry().unwrap();
+                let unit = if self.mode() == Mode::Time { "s" } else { " words" };
+                let value = self.lengths.value(entry).unwrap_or_default();
+                format!("◀ {value}{unit} ▶")
+            },
+            Mode::Quote => "a random quote".to_string(),
+            Mode::Code => "a random snippet".to_string(),
+            Mode::Custom => "any number of words".to_string(),
+        }
+    }
+
+    fn start(&self) -> AppAction {
+        let value = self.mode().length_entry()
+            .and_then(|entry| self.lengths.value(entry))
+            .unwrap_or_default();
+
+        match self.mode() {
+            Mode::Time => AppAction::GoTo(LoadablePage::Game(GameMode::Timed(value))),
+            Mode::Words => AppAction::GoTo(LoadablePage::Game(GameMode::Words(value as usize))),
+            Mode::Quote => AppAction::GoTo(LoadablePage::Game(GameMode::Quote)),
+            Mode::Code => AppAction::GoTo(LoadablePage::Game(GameMode::Code)),
+            Mode::Custom => AppAction::Dialog(custom_length_dialog()),
+        }
+    }
+
+    fn stats_lines(&self) -> Vec<Line<'_>> {
+        let mut lines = vec![Line::styled("Last run", STYLE_HEADING)];
+
+        match self.history.last() {
+            Some(run) => {
+                lines.push(Line::from(format!("WPM: {:.0}  {:.1}%", run.wpm, run.accuracy)));
+                lines.push(Line::from(format!("{} words in {}", run.words, format_time(run.duration))));
+            },
+            None => lines.push(Line::styled("No runs yet.", STYLE_DESCRIPTION)),
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::styled("Today", STYLE_HEADING));
+
+        let today = self.history.day_summary(history::now_timestamp());
+        if today.runs == 0 {
+            lines.push(Line::styled("No runs yet.", STYLE_DESCRIPTION));
+        } else {
+            let runs = if today.runs == 1 { "run" } else { "runs" };
+            lines.push(Line::from(format!("{} {runs}, {}", today.runs, format_time(today.time))));
+            lines.push(Line::from(format!("avg {:.0}, best {:.0} WPM", today.average_wpm, today.best_wpm)));
+        }
+
+        lines
+    }
+
+    fn draw_mode_bar(&mut self, frame: &mut Frame, area: Rect) {
+        let constraints = MODES.iter().map(|mode| Constraint::Length(mode.name().len() as u16 + 2));
+        let areas = Layout::horizontal(constraints)
+            .flex(Flex::Center)
+            .spacing(1)
+            .split(area);
+
+        for (index, (mode, &mode_area)) in MODES.iter().zip(areas.iter()).enumerate() {
+            let style = match (index == self.mode, self.is_menu_focused()) {
+                (true, false) => STYLE_MODE_SELECTED,
+                (true, true) => STYLE_MODE_PICKED,
+                (false, _) => STYLE_MODE,
+            };
+            frame.render_widget(Paragraph::new(format!(" {} ", mode.name())).style(style), mode_area);
+        }
+
+        self.mode_areas = areas.to_vec();
+    }
+
+    fn handle_mouse(&mut self, dispatcher: AppEventDispatcher, event: &cse::MouseEvent) -> PageHandleEvent {
+        let position = Position::new(event.column, event.row);
+
+        if let Some(index) = self.mode_areas.iter().position(|area| area.contains(position)) {
+            if event.kind == cse::MouseEventKind::Down(cse::MouseButton::Left) && !self.menu.in_submenu() {
+                self.mode = index;
+                self.menu_focused = false;
+            }
+            return PageHandleEvent::Consume
+        }
+
+        if self.length_area.contains(position) {
+            let center = self.length_area.x + self.length_area.width / 2;
+            match event.kind {
+                cse::MouseEventKind::Down(cse::MouseButton::Left) if event.column < center => self.step_length(-1),
+                cse::MouseEventKind::Down(cse::MouseButton::Left) => self.step_length(1),
+                cse::MouseEventKind::ScrollDown => self.step_length(-1),
+                cse::MouseEventKind::ScrollUp => self.step_length(1),
+                _ => {},
+            }
+            return PageHandleEvent::Consume
+        }
+
+        self.menu.handle_event(dispatcher, &AppEvent::Crossterm(cse::Event::Mouse(*event)))
+    }
+
+    fn handle_menu_key(
+        &mut self,
+        dispatcher: AppEventDispatcher,
+        event: &AppEvent,
+        key_event: &cse::KeyEvent,
+    ) -> PageHandleEvent {
+        match key_event.code {
+            cse::KeyCode::Tab | cse::KeyCode::BackTab | cse::KeyCode::Esc if !self.menu.in_submenu() => {
+                self.menu_focused = false;
+                PageHandleEvent::Consume
+            },
+            _ => self.menu.handle_event(dispatcher, event),
+        }
+    }
+}
+
+impl Default for HomePage {
+    fn default() -> Self {
+        HomePage::new()
     }
 }
 
-impl From<&MenuItem> for ListItem<'_> {
-    fn from(item: &MenuItem) -> Self {
-        ListItem::new(item.name.clone())
+impl Page for HomePage {
+    fn on_resume(&mut self) {
+        self.history = History::load().unwrap_or_default();
+        self.menu.on_resume();
     }
+
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let [bar_area, length_area, _, content] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ]).areas(rect);
+
+        self.draw_mode_bar(frame, bar_area);
+
+        let length = Paragraph::new(self.length_text())
+            .style(STYLE_LENGTH)
+            .centered();
+        frame.render_widget(length, length_area);
+        self.length_area = length_area;
+
+        let [menu_area, stats_area] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(STATS_WIDTH),
+        ]).areas(content);
+
+        self.menu.set_focused(self.is_menu_focused());
+        self.menu.draw(frame, menu_area);
+
+        let stats = Paragraph::new(self.stats_lines())
+            .block(Block::new().borders(Borders::LEFT).padding(Padding::horizontal(1)));
+        frame.render_widget(stats, stats_area);
+    }
+
+    fn handle_event(&mut self, dispatcher: AppEventDispatcher, event: &AppEvent) -> PageHandleEvent {
+        let AppEvent::Crossterm(cse_event) = event else {
+            return PageHandleEvent::None
+        };
+
+        match cse_event {
+            cse::Event::Key(key_event) if key_event.kind == cse::KeyEventKind::Press => {
+                if self.is_menu_focused() {
+                    return self.handle_menu_key(dispatcher, event, key_event)
+                }
+
+                match key_event.code {
+                    cse::KeyCode::Tab => self.step_mode(1),
+                    cse::KeyCode::BackTab => self.step_mode(-1),
+                    cse::KeyCode::Left => self.step_length(-1),
+                    cse::KeyCode::Right => self.step_length(1),
+                    // Space starts too, the menu would take it as Enter on
+                    // its first entry.
+                    cse::KeyCode::Enter | cse::KeyCode::Char(' ') => {
+                        dispatcher.dispatch(AppEvent::App(self.start()));
+                    },
+                    cse::KeyCode::Char('j' | 'k') | cse::KeyCode::Up | cse::KeyCode::Down => {
+                        self.menu_focused = true;
+                    },
+                    // Shortcuts work from the mode bar too.
+                    _ => return self.menu.handle_event(dispatcher, event),
+                }
+
+                PageHandleEvent::Consume
+            },
+            cse::Event::Mouse(mouse_event) => self.handle_mouse(dispatcher, mouse_event),
+            _ => PageHandleEvent::None,
+        }
+    }
+
+    fn footer_hints(&self) -> Vec<(&'static str, &'static str)> {
+        if self.is_menu_focused() {
+            return vec![("Tab", "pick a mode"), ("Enter", "select")];
+        }
+
+        vec![("Tab/←/→", "pick"), ("Enter", "start")]
+    }
+
+    fn page_title(&self) -> &str {
+        "Home"
+    }
+}
+
+fn format_time(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
 }
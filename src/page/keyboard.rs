@@ -0,0 +1,131 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Style, Modifier, Color};
+use ratatui::widgets::Widget;
+
+use crate::layout::{COLUMNS, Finger, KeyboardLayout, Row};
+
+/// Rows the keyboard takes: three key rows, space and the finger hint.
+pub const KEYBOARD_HEIGHT: u16 = 5;
+
+const KEY_WIDTH: u16 = 4;
+
+/// Row stagger of a real keyboard, in columns.
+const ROW_OFFSETS: [u16; 3] = [0, 1, 3];
+
+const SPACE_WIDTH: u16 = 5 * KEY_WIDTH - 1;
+
+const STYLE_KEY: Style = Style::new()
+    .fg(Color::Gray);
+
+const STYLE_FINGER: Style = Style::new()
+    .fg(Color::Yellow);
+
+const STYLE_NEXT: Style = Style::new()
+    .bg(Color::Green)
+    .fg(Color::Black)
+    .add_modifier(Modifier::BOLD);
+
+const STYLE_WRONG: Style = Style::new()
+    .bg(Color::Red)
+    .fg(Color::Black);
+
+const STYLE_HINT: Style = Style::new()
+    .fg(Color::Yellow);
+
+/// Keyboard layout with the next key and its finger marked, and the
+/// last wrong key in red.
+#[derive(Debug)]
+pub struct Keyboard<'a> {
+    layout: &'a KeyboardLayout,
+    next: Option<char>,
+    wrong: Option<char>,
+}
+
+impl<'a> Keyboard<'a> {
+    pub fn new(layout: &'a KeyboardLayout) -> Self {
+        Keyboard {
+            layout,
+            next: None,
+            wrong: None,
+        }
+    }
+
+    pub fn next(mut self, next: Option<char>) -> Self {
+        self.next = next;
+        self
+    }
+
+    pub fn wrong(mut self, wrong: Option<char>) -> Self {
+        self.wrong = wrong;
+        self
+    }
+
+    fn key_style(&self, key: char, finger: Finger) -> Style {
+        let is = |c: Option<char>| c.and_then(|c| c.to_lowercase().next()) == Some(key);
+
+        if is(self.wrong) {
+            STYLE_WRONG
+        } else if is(self.next) {
+            STYLE_NEXT
+        } else if self.next.and_then(|c| self.layout.finger(c)) == Some(finger) {
+            STYLE_FINGER
+        } else {
+            STYLE_KEY
+        }
+    }
+
+    fn hint(&self) -> String {
+        let Some(next) = self.next else {
+            return String::new();
+        };
+
+        let Some(finger) = self.layout.finger(next) else {
+            return format!("{next:?} is not on {}", self.layout.name);
+        };
+
+        if next.is_uppercase() {
+            format!("{finger} + shift")
+        } else {
+            finger.to_string()
+        }
+    }
+}
+
+impl Widget for Keyboard<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = COLUMNS as u16 * KEY_WIDTH + ROW_OFFSETS[2];
+        let left = area.x + area.width.saturating_sub(width) / 2;
+        let fits = |x: u16, len: u16, y: u16| x + len <= area.right() && y < area.bottom();
+
+        for (row, offset) in Row::ALL.into_iter().zip(ROW_OFFSETS) {
+            let y = area.y + row as u16;
+
+            for (column, &key) in self.layout.row(row).iter().enumerate() {
+                let x = left + offset + column as u16 * KEY_WIDTH;
+
+                if fits(x, KEY_WIDTH - 1, y) {
+                    let style = self.key_style(key, Finger::for_column(column));
+                    buf.set_string(x, y, format!(" {key} "), style);
+                }
+            }
+        }
+
+        let space_x = left + ROW_OFFSETS[2] + 2 * KEY_WIDTH;
+        let space_y = area.y + 3;
+
+        if fits(space_x, SPACE_WIDTH, space_y) {
+            let style = self.key_style(' ', Finger::Thumb);
+            let label = format!("{:^width$}", "space", width = SPACE_WIDTH as usize);
+            buf.set_string(space_x, space_y, label, style);
+        }
+
+        let hint = self.hint();
+        let hint_y = area.y + 4;
+        let hint_x = area.x + area.width.saturating_sub(hint.chars().count() as u16) / 2;
+
+        if hint_y < area.bottom() {
+            buf.set_stringn(hint_x, hint_y, hint, area.width as usize, STYLE_HINT);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crossterm::event as cse;
+
+use ratatui::Frame;
+use ratatui::layout::{Rect, Layout, Constraint};
+use ratatui::style::{Style, Modifier, Color};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListState, ListItem, Paragraph, Wrap};
+
+use crate::dialog::Dialog;
+use crate::events::{AppEventDispatcher, AppAction, AppEvent};
+use crate::layout::KeyboardLayout;
+use crate::lessons::{LessonProgress, LESSONS};
+use crate::settings::Settings;
+use super::{list_item_at, LoadablePage, Page, PageHandleEvent};
+
+const SELECTED_STYLE: Style = Style::new()
+    .add_modifier(Modifier::BOLD)
+    .add_modifier(Modifier::UNDERLINED);
+
+const STYLE_COMPLETED: Style = Style::new()
+    .fg(Color::Green);
+
+const STYLE_LOCKED: Style = Style::new()
+    .fg(Color::DarkGray);
+
+/// List of lessons with their progress, opens the selected one.
+#[derive(Debug)]
+pub struct LessonsPage {
+    layout: KeyboardLayout,
+    title: String,
+    progress: LessonProgress,
+    state: ListState,
+    list_area: Rect,
+}
+
+impl LessonsPage {
+    pub fn new(settings: &Settings) -> Self {
+        let layout = settings.layout.clone();
+        let title = format!("Lessons ({})", layout.name);
+        let progress = LessonProgress::load(&layout).unwrap_or_default();
+        let mut state = ListState::default();
+
+        // Start on the first lesson that still needs work.
+        let next = (0..LESSONS.len())
+            .find(|&index| !progress.is_completed(index))
+            .unwrap_or(0);
+        state.select(Some(next));
+
+        LessonsPage {
+            layout,
+            title,
+            progress,
+            state,
+            list_area: Rect::default(),
+        }
+    }
+
+    fn item(&self, index: usize) -> ListItem<'static> {
+        let lesson = &LESSONS[index];
+        let (marker, style) = if self.progress.is_completed(index) {
+            ("✓ ", STYLE_COMPLETED)
+        } else if self.progress.is_unlocked(index) {
+            ("  ", Style::new())
+        } else {
+            // The lock is two columns wide, the others are padded to match.
+            ("🔒", STYLE_LOCKED)
+        };
+
+        let best = self.progress.scores.get(&index)
+            .map(|score| format!("  best {:.0} WPM {:.0}%", score.wpm, score.accuracy))
+            .unwrap_or_default();
+
+        ListItem::new(Line::from(vec![
+            Span::styled(format!("{marker} {:>2}. {}", index + 1, lesson.name), style),
+            Span::styled(best, STYLE_LOCKED),
+        ]))
+    }
+
+    fn open_selected(&self, dispatcher: AppEventDispatcher) {
+        let Some(index) = self.state.selected() else {
+            return
+        };
+
+        let action = if self.progress.is_unlocked(index) {
+            AppAction::GoTo(LoadablePage::Lesson(index))
+        } else {
+            AppAction::Dialog(Dialog::message(
+                "Locked",
+                "Complete the previous lesson to unlock this one.",
+            ))
+        };
+
+        dispatcher.dispatch(AppEvent::App(action));
+    }
+
+    fn select_next(&mut self) {
+        let next = self.state.selected().map_or(0, |index| index + 1);
+        self.state.select(Some(next.min(LESSONS.len() - 1)));
+    }
+
+    fn handle_mouse(&mut self, dispatcher: AppEventDispatcher, event: &cse::MouseEvent) -> PageHandleEvent {
+        match event.kind {
+            cse::MouseEventKind::Down(cse::MouseButton::Left) => {
+                let Some(index) = list_item_at(self.list_area, &self.state, LESSONS.len(), event.column, event.row) else {
+                    return PageHandleEvent::None
+                };
+
+                self.state.select(Some(index));
+                self.open_selected(dispatcher);
+            },
+            cse::MouseEventKind::ScrollUp => {
+                self.state.select_previous();
+            },
+            cse::MouseEventKind::ScrollDown => {
+                self.select_next();
+            },
+            _ => return PageHandleEvent::None,
+        }
+
+        PageHandleEvent::Consume
+    }
+
+    fn details(&self) -> String {
+        let Some(index) = self.state.selected() else {
+            return String::new();
+        };
+
+        let lesson = &LESSONS[index];
+        let keys = lesson.keys(&self.layout).into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let status = if self.progress.is_unlocked(index) {
+            "Press Enter to start."
+        } else {
+            "Complete the previous lesson to unlock."
+        };
+
+        format!(
+            "New keys: {keys}  Goal: {:.0} WPM, {:.0}% accuracy. {status}",
+            lesson.target_wpm,
+            lesson.target_accuracy,
+        )
+    }
+}
+
+impl Page for LessonsPage {
+    fn on_resume(&mut self) {
+        self.progress = LessonProgress::load(&self.layout).unwrap_or_default();
+    }
+
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let [details, list_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(1),
+        ]).areas::<2>(rect);
+
+        let details_text = Paragraph::new(self.details())
+            .wrap(Wrap { trim: true });
+
+        frame.render_widget(details_text, details);
+
+        let items: Vec<ListItem> = (0..LESSONS.len())
+            .map(|index| self.item(index))
+            .collect();
+        let list = List::new(items)
+            .highlight_style(SELECTED_STYLE);
+
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+        self.list_area = list_area;
+    }
+
+    fn handle_event(&mut self, dispatcher: AppEventDispatcher, event: &AppEvent) -> PageHandleEvent {
+        let key_event = match event {
+            AppEvent::Crossterm(cse::Event::Key(key_event)) => key_event,
+            AppEvent::Crossterm(cse::Event::Mouse(mouse_event)) => {
+                return self.handle_mouse(dispatcher, mouse_event)
+            },
+            _ => return PageHandleEvent::None,
+        };
+
+        if key_event.kind != cse::KeyEventKind::Press {
+            return PageHandleEvent::None
+        }
+
+        match key_event.code {
+            cse::KeyCode::Char('k') | cse::KeyCode::Up => {
+                self.state.select_previous();
+            },
+            cse::KeyCode::Char('j') | cse::KeyCode::Down => {
+                self.select_next();
+            },
+            cse::KeyCode::Enter => {
+                self.open_selected(dispatcher);
+            },
+            _ => return PageHandleEvent::None,
+        }
+
+        PageHandleEvent::Consume
+    }
+
+    fn footer_hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![("j/k", "move"), ("Enter", "start")]
+    }
+
+    fn page_title(&self) -> &str {
+        &self.title
+    }
+}
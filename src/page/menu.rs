@@ -0,0 +1,218 @@
+use std::fmt;
+
+use ratatui::style::{Style, Color};
+use ratatui::text::{Line, Span};
+
+use crate::events::AppAction;
+
+const STYLE_SHORTCUT: Style = Style::new()
+    .fg(Color::Yellow);
+
+const STYLE_DISABLED: Style = Style::new()
+    .fg(Color::DarkGray);
+
+/// Builds an action from the menu it was picked in, so it can read the
+/// values of the menu's choices.
+pub type MenuFn = Box<dyn Fn(&Menu) -> AppAction>;
+
+pub enum EntryKind {
+    Action(AppAction),
+    Run(MenuFn),
+    Submenu(Menu),
+    /// Value picked in place with left and right.
+    Choice {
+        values: Vec<u64>,
+        unit: &'static str,
+        selected: usize,
+    },
+}
+
+impl fmt::Debug for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryKind::Action(action) => f.debug_tuple("Action").field(action).finish(),
+            EntryKind::Run(_) => f.write_str("Run(..)"),
+            EntryKind::Submenu(menu) => f.debug_tuple("Submenu").field(menu).finish(),
+            EntryKind::Choice { values, unit, selected } => f.debug_struct("Choice")
+                .field("values", values)
+                .field("unit", unit)
+                .field("selected", selected)
+                .finish(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MenuEntry {
+    pub name: String,
+    pub description: String,
+    pub shortcut: Option<char>,
+    pub enabled: bool,
+    /// Decides `enabled` whenever the menu shows up again.
+    pub available: Option<fn() -> bool>,
+    pub kind: EntryKind,
+}
+
+// Constructors
+impl MenuEntry {
+    fn new(name: &str, kind: EntryKind) -> Self {
+        MenuEntry {
+            name: name.to_string(),
+            description: String::new(),
+            shortcut: None,
+            enabled: true,
+            available: None,
+            kind,
+        }
+    }
+
+    pub fn action(name: &str, action: AppAction) -> Self {
+        MenuEntry::new(name, EntryKind::Action(action))
+    }
+
+    pub fn run(name: &str, run: impl Fn(&Menu) -> AppAction + 'static) -> Self {
+        MenuEntry::new(name, EntryKind::Run(Box::new(run)))
+    }
+
+    pub fn submenu(name: &str, menu: Menu) -> Self {
+        MenuEntry::new(name, EntryKind::Submenu(menu))
+    }
+
+    /// Panics if `values` is empty, a choice always shows one of them.
+    pub fn choice(name: &str, values: &[u64], unit: &'static str, selected: usize) -> Self {
+        assert!(!values.is_empty(), "choice `{name}` has no values");
+
+        MenuEntry::new(name, EntryKind::Choice {
+            values: values.to_vec(),
+            unit,
+            selected: selected.min(values.len() - 1),
+        })
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    pub fn shortcut(mut self, shortcut: char) -> Self {
+        self.shortcut = Some(shortcut);
+        self
+    }
+
+    pub fn available(mut self, available: fn() -> bool) -> Self {
+        self.enabled = available();
+        self.available = Some(available);
+        self
+    }
+}
+
+impl MenuEntry {
+    /// Step a choice by `delta` values, wrapping around.
+    pub fn step(&mut self, delta: isize) {
+        if let EntryKind::Choice { values, selected, .. } = &mut self.kind {
+            let len = values.len() as isize;
+            *selected = (*selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    pub fn line(&self) -> Line<'_> {
+        let shortcut = self.shortcut.map_or(" ".to_string(), String::from);
+        let label = match &self.kind {
+            EntryKind::Submenu(_) => format!("{} ▸", self.name),
+            EntryKind::Choice { values, unit, selected } => {
+                format!("{}  ◀ {}{unit} ▶", self.name, values[*selected])
+            },
+            EntryKind::Action(_) | EntryKind::Run(_) => self.name.clone(),
+        };
+
+        if !self.enabled {
+            return Line::styled(format!("{shortcut} {label}"), STYLE_DISABLED);
+        }
+
+        Line::from(vec![
+            Span::styled(shortcut, STYLE_SHORTCUT),
+            Span::from(" "),
+            Span::from(label),
+        ])
+    }
+}
+
+/// A list of entries, declared up front and walked by `MenuPage`.
+#[derive(Debug)]
+pub struct Menu {
+    pub title: String,
+    pub entries: Vec<MenuEntry>,
+}
+
+impl Menu {
+    pub fn new(title: &str) -> Self {
+        Menu {
+            title: title.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn entry(mut self, entry: MenuEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Current value of the choice called `name`.
+    pub fn value(&self, name: &str) -> Option<u64> {
+        self.entries.iter().find_map(|entry| match &entry.kind {
+            EntryKind::Choice { values, selected, .. } if entry.name == name => {
+                values.get(*selected).copied()
+            },
+            _ => None,
+        })
+    }
+
+    /// Check again which entries are available, submenus included.
+    pub fn refresh(&mut self) {
+        for entry in self.entries.iter_mut() {
+            if let Some(available) = entry.available {
+                entry.enabled = available();
+            }
+
+            if let EntryKind::Submenu(menu) = &mut entry.kind {
+                menu.refresh();
+            }
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.entries.iter()
+            .map(|entry| entry.line().width())
+            .max()
+            .unwrap_or(0) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices_wrap_and_are_read_by_name() {
+        let mut menu = Menu::new("Play")
+            .entry(MenuEntry::choice("Time", &[15, 30, 60], "s", 1))
+            .entry(MenuEntry::action("Quit", AppAction::Exit).available(|| false));
+
+        assert_eq!(menu.value("Time"), Some(30));
+        assert_eq!(menu.value("Quit"), None);
+
+        menu.entries[0].step(2);
+        assert_eq!(menu.value("Time"), Some(15));
+        menu.entries[0].step(-1);
+        assert_eq!(menu.value("Time"), Some(60));
+        assert_eq!(menu.entries[0].line().to_string(), "  Time  ◀ 60s ▶");
+
+        assert!(!menu.entries[1].enabled);
+    }
+
+    #[test]
+    #[should_panic(expected = "has no values")]
+    fn choices_need_values() {
+        MenuEntry::choice("Time", &[], "s", 0);
+    }
+}
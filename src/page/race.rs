@@ -0,0 +1,308 @@
+use crossterm::event as cse;
+use std::time::{Duration, Instant};
+
+use ratatui::Frame;
+use ratatui::layout::{Rect, Layout, Constraint};
+use ratatui::style::{Style, Modifier, Color};
+use ratatui::widgets::{Paragraph, LineGauge};
+
+use crate::events::{AppEventDispatcher, AppEvent};
+use crate::race::{RaceAddr, RaceClient, RaceEvent, RaceRequest};
+use crate::settings::Settings;
+use super::{GamePage, Page, PageHandleEvent};
+
+const STYLE_STATUS: Style = Style::new()
+    .add_modifier(Modifier::BOLD);
+
+const STYLE_ERROR: Style = Style::new()
+    .fg(Color::Red);
+
+const STYLE_BAR: Style = Style::new()
+    .fg(Color::Cyan);
+
+const STYLE_OWN_BAR: Style = Style::new()
+    .fg(Color::Green);
+
+#[derive(Debug, PartialEq, Eq)]
+enum RaceState {
+    Connecting,
+    Lobby,
+    Countdown(u32),
+    Racing,
+    Finished,
+    Disconnected(String),
+}
+
+#[derive(Debug)]
+struct Racer {
+    id: u32,
+    name: String,
+    words: usize,
+    time: Option<Duration>,
+}
+
+/// Typing race against other stamba instances connected to a lobby.
+#[derive(Debug)]
+pub struct RacePage {
+    addr: RaceAddr,
+    name: String,
+    settings: Settings,
+
+    client: Option<RaceClient>,
+    state: RaceState,
+    id: Option<u32>,
+    text: String,
+    racers: Vec<Racer>,
+    game: Option<GamePage>,
+}
+
+impl RacePage {
+    pub fn new(addr: RaceAddr, settings: &Settings) -> Self {
+        RacePage {
+            addr,
+            name: settings.player_name(),
+            settings: settings.clone(),
+
+            client: None,
+            state: RaceState::Connecting,
+            id: None,
+            text: String::new(),
+            racers: Vec::new(),
+            game: None,
+        }
+    }
+
+    fn send(&self, request: RaceRequest) {
+        if let Some(client) = &self.client {
+            client.send(request);
+        }
+    }
+
+    fn racer_mut(&mut self, id: u32) -> Option<&mut Racer> {
+        self.racers.iter_mut().find(|racer| racer.id == id)
+    }
+
+    fn new_game(&self) -> GamePage {
+        let mut game = GamePage::new(self.text.clone(), self.settings.session_options());
+        // Races go against other people, not into the history, practice
+        // stats or replays.
+        game.record = false;
+        game
+    }
+
+    fn handle_race_event(&mut self, event: &RaceEvent) {
+        match event {
+            RaceEvent::Welcome { id, text } => {
+                self.id = Some(*id);
+                self.text = text.clone();
+                self.game = Some(self.new_game());
+                self.state = RaceState::Lobby;
+            },
+            RaceEvent::Joined { id, name } => {
+                self.racers.push(Racer {
+                    id: *id,
+                    name: name.clone(),
+                    words: 0,
+                    time: None,
+                });
+            },
+            RaceEvent::Left(id) => {
+                self.racers.retain(|racer| racer.id != *id);
+            },
+            RaceEvent::Countdown(secs) => {
+                self.state = RaceState::Countdown(*secs);
+            },
+            RaceEvent::Start => {
+                for racer in &mut self.racers {
+                    racer.words = 0;
+                    racer.time = None;
+                }
+
+                let mut game = self.new_game();
+                game.start(Instant::now());
+                self.game = Some(game);
+                self.state = RaceState::Racing;
+            },
+            RaceEvent::Progress { id, words } => {
+                if let Some(racer) = self.racer_mut(*id) {
+                    racer.words = *words;
+                }
+            },
+            RaceEvent::Finished { id, time } => {
+                let words = self.text.split_whitespace().count();
+
+                if let Some(racer) = self.racer_mut(*id) {
+                    racer.words = words;
+                    racer.time = Some(*time);
+                }
+
+                let everyone_done = self.racers.iter()
+                    .all(|racer| racer.time.is_some());
+
+                if everyone_done {
+                    self.state = RaceState::Finished;
+                }
+            },
+            RaceEvent::Disconnected(reason) => {
+                self.state = RaceState::Disconnected(reason.clone());
+                self.client = None;
+            },
+        }
+    }
+
+    fn handle_key(&mut self, key_event: &cse::KeyEvent) -> PageHandleEvent {
+        match self.state {
+            RaceState::Lobby | RaceState::Finished => {
+                if key_event.code == cse::KeyCode::Enter {
+                    self.send(RaceRequest::Start);
+                    return PageHandleEvent::Consume
+                }
+
+                PageHandleEvent::None
+            },
+            RaceState::Racing => {
+                let Some(game) = &mut self.game else {
+                    return PageHandleEvent::None
+                };
+
+                if game.session.is_done() {
+                    return PageHandleEvent::None
+                }
+
+                let now = Instant::now();
+                let words = game.session.current_word();
+                let handled = game.handle_key(key_event, now);
+
+                if game.session.is_done() {
+                    let time = game.session.elapsed(now);
+                    self.send(RaceRequest::Finish(time));
+                } else if game.session.current_word() != words {
+                    let words = game.session.current_word();
+                    self.send(RaceRequest::Progress(words));
+                }
+
+                handled
+            },
+            _ => PageHandleEvent::None,
+        }
+    }
+
+    fn status(&self) -> String {
+        match &self.state {
+            RaceState::Connecting => format!("Connecting to {}...", self.addr),
+            RaceState::Lobby => {
+                let count = self.racers.len();
+                format!("Lobby: {count} racer(s). Press Enter to start the race.")
+            },
+            RaceState::Countdown(secs) => format!("Starting in {secs}..."),
+            RaceState::Racing => "Race!".to_string(),
+            RaceState::Finished => {
+                "Race finished. Press Enter for a rematch.".to_string()
+            },
+            RaceState::Disconnected(reason) => format!("Disconnected: {reason}"),
+        }
+    }
+
+    fn draw_racers(&self, frame: &mut Frame, area: Rect) {
+        let total = self.text.split_whitespace().count().max(1);
+        let rows = Layout::vertical(
+            self.racers.iter().map(|_| Constraint::Length(1))
+        ).split(area);
+
+        for (racer, row) in self.racers.iter().zip(rows.iter()) {
+            let ratio = (racer.words as f64 / total as f64).min(1.0);
+            let time = racer.time
+                .map(|time| format!(" {:.1}s", time.as_secs_f64()))
+                .unwrap_or_default();
+            let style = if Some(racer.id) == self.id {
+                STYLE_OWN_BAR
+            } else {
+                STYLE_BAR
+            };
+
+            let gauge = LineGauge::default()
+                .filled_style(style)
+                .label(format!("{:>12} {:>3}/{total}{time}", racer.name, racer.words))
+                .ratio(ratio);
+
+            frame.render_widget(gauge, *row);
+        }
+    }
+}
+
+impl Page for RacePage {
+    fn on_load(&mut self, dispatcher: AppEventDispatcher) {
+        let client = RaceClient::connect(self.addr.clone(), self.name.clone(), dispatcher);
+        self.client = Some(client);
+    }
+
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let [status_area, racers_area, game_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(self.racers.len() as u16 + 1),
+            Constraint::Min(1),
+        ]).areas::<3>(rect);
+
+        let style = if let RaceState::Disconnected(_) = self.state {
+            STYLE_ERROR
+        } else {
+            STYLE_STATUS
+        };
+
+        frame.render_widget(Paragraph::new(self.status()).style(style), status_area);
+        self.draw_racers(frame, racers_area);
+
+        if let Some(game) = &mut self.game {
+            game.draw(frame, game_area);
+        }
+    }
+
+    fn handle_event(&mut self, _: AppEventDispatcher, event: &AppEvent) -> PageHandleEvent {
+        match event {
+            AppEvent::Race(race_event) => {
+                self.handle_race_event(race_event);
+                PageHandleEvent::Consume
+            },
+            AppEvent::Crossterm(cse::Event::Key(key_event))
+                if key_event.kind == cse::KeyEventKind::Press => {
+                self.handle_key(key_event)
+            },
+            _ => {
+                PageHandleEvent::None
+            },
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        self.state == RaceState::Racing
+            && self.game.as_ref().is_some_and(|game| !game.session.is_done())
+    }
+
+    fn footer_hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.state {
+            RaceState::Lobby | RaceState::Finished => vec![("Enter", "start")],
+            _ => Vec::new(),
+        }
+    }
+
+    fn page_title(&self) -> &str {
+        "Race"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn racing_is_busy_and_not_recorded() {
+        let addr = RaceAddr::Unix("/nonexistent".into());
+        let mut page = RacePage::new(addr, &Settings::default());
+        page.handle_race_event(&RaceEvent::Welcome { id: 0, text: "ab".to_string() });
+        assert!(!page.is_busy());
+
+        page.handle_race_event(&RaceEvent::Start);
+        assert!(page.is_busy());
+        assert!(!page.game.as_ref().unwrap().record);
+    }
+}
@@ -0,0 +1,210 @@
+use crossterm::event as cse;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ratatui::Frame;
+use ratatui::layout::{Rect, Layout, Constraint};
+use ratatui::style::{Style, Color};
+use ratatui::widgets::Paragraph;
+
+use crate::events::{AppEventDispatcher, AppEvent};
+use crate::replay::Replay;
+use super::{GamePage, Page, PageHandleEvent};
+
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 8.0;
+
+const STYLE_STATUS: Style = Style::new()
+    .fg(Color::Gray);
+
+const STYLE_ERROR: Style = Style::new()
+    .fg(Color::Red);
+
+/// Plays back a recorded run through a regular `GamePage`.
+#[derive(Debug)]
+pub struct ReplayPage {
+    state: Result<ReplayState, String>,
+}
+
+#[derive(Debug)]
+struct ReplayState {
+    replay: Replay,
+    game: GamePage,
+    /// Instant the replayed game pretends to have started at.
+    origin: Instant,
+    next_key: usize,
+    position: Duration,
+    last_tick: Option<Instant>,
+    speed: f64,
+    paused: bool,
+}
+
+impl ReplayState {
+    fn new(replay: Replay) -> Self {
+        let origin = Instant::now();
+        let mut game = GamePage::from_replay(&replay);
+        game.start(origin);
+
+        ReplayState {
+            replay,
+            game,
+            origin,
+            next_key: 0,
+            position: Duration::ZERO,
+            last_tick: None,
+            speed: 1.0,
+            paused: false,
+        }
+    }
+
+    fn restart(&mut self) {
+        let speed = self.speed;
+        let replay = self.replay.clone();
+
+        *self = ReplayState::new(replay);
+        self.speed = speed;
+    }
+
+    fn tick(&mut self, now: Instant) {
+        let last_tick = self.last_tick.replace(now).unwrap_or(now);
+
+        if self.paused {
+            return;
+        }
+
+        self.position += now.duration_since(last_tick).mul_f64(self.speed);
+
+        while let Some(key) = self.replay.keys.get(self.next_key) {
+            if key.at > self.position {
+                break;
+            }
+
+            self.game.handle_key(&key.key_event(), self.origin + key.at);
+            self.next_key += 1;
+        }
+
+        // A timed run ends at its limit, after the last key.
+        self.game.check_time_limit(self.origin + self.position);
+    }
+
+    fn status(&self) -> String {
+        let state = if self.paused { "paused" } else { "playing" };
+        let position = self.position.min(self.replay.duration()).as_secs_f64();
+        let duration = self.replay.duration().as_secs_f64();
+
+        format!("Replay {state} {:.2}x  {position:.1}s / {duration:.1}s", self.speed)
+    }
+
+    fn handle_key(&mut self, key_event: &cse::KeyEvent) -> PageHandleEvent {
+        match key_event.code {
+            cse::KeyCode::Char('+') | cse::KeyCode::Char('=') => {
+                self.speed = (self.speed * 2.0).min(MAX_SPEED);
+            },
+            cse::KeyCode::Char('-') => {
+                self.speed = (self.speed / 2.0).max(MIN_SPEED);
+            },
+            cse::KeyCode::Char(' ') => {
+                self.paused = !self.paused;
+            },
+            cse::KeyCode::Char('r') => {
+                self.restart();
+            },
+            _ => {
+                return PageHandleEvent::None
+            },
+        }
+
+        PageHandleEvent::Consume
+    }
+}
+
+impl ReplayPage {
+    pub fn open(path: &Path) -> Self {
+        let state = Replay::load(path)
+            .map(ReplayState::new)
+            .map_err(|err| format!("Could not load {}: {err}", path.display()));
+
+        ReplayPage { state }
+    }
+}
+
+impl Page for ReplayPage {
+    fn draw(&mut self, frame: &mut Frame, rect: Rect) {
+        let state = match &mut self.state {
+            Ok(state) => state,
+            Err(err) => {
+                let error = Paragraph::new(err.as_str())
+                    .style(STYLE_ERROR);
+
+                frame.render_widget(error, rect);
+                return;
+            },
+        };
+
+        let [status_area, game_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(1),
+        ]).areas::<2>(rect);
+
+        let status = Paragraph::new(state.status())
+            .style(STYLE_STATUS);
+
+        frame.render_widget(status, status_area);
+        state.game.draw(frame, game_area);
+    }
+
+    fn handle_event(&mut self, _: AppEventDispatcher, event: &AppEvent) -> PageHandleEvent {
+        let Ok(state) = &mut self.state else {
+            return PageHandleEvent::None
+        };
+
+        match event {
+            AppEvent::Tick => {
+                state.tick(Instant::now());
+                PageHandleEvent::Consume
+            },
+            AppEvent::Crossterm(cse::Event::Key(key_event))
+                if key_event.kind == cse::KeyEventKind::Press => {
+                state.handle_key(key_event)
+            },
+            _ => {
+                PageHandleEvent::None
+            },
+        }
+    }
+
+    fn footer_hints(&self) -> Vec<(&'static str, &'static str)> {
+        if self.state.is_err() {
+            return Vec::new()
+        }
+
+        vec![("+/-", "change speed"), ("Space", "pause"), ("r", "restart")]
+    }
+
+    fn page_title(&self) -> &str {
+        "Replay"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyEvent, KeyModifiers};
+
+    #[test]
+    fn timed_replay_reaches_the_results() {
+        let mut replay = Replay::new("ab cd ef".to_string(), Default::default(), false);
+        replay.limit = Some(Duration::from_secs(1));
+        let key = KeyEvent::new(cse::KeyCode::Char('a'), KeyModifiers::NONE);
+        replay.record(Duration::from_millis(200), &key);
+
+        let mut state = ReplayState::new(replay);
+        let origin = state.origin;
+        state.tick(origin);
+        state.tick(origin + Duration::from_millis(500));
+        assert!(!state.game.session.is_done());
+
+        state.tick(origin + Duration::from_secs(2));
+        assert!(state.game.session.is_done());
+    }
+}
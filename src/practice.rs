@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::time::Duration;
+
+use stamba::engine::SessionEvent;
+
+use crate::drill::{self, Rng};
+use crate::layout::{Finger, KeyboardLayout};
+use crate::storage;
+
+const PRACTICE_FILE: &str = "practice.tsv";
+
+/// Stats are scaled down before every run, so old mistakes fade out.
+const DECAY: f64 = 0.9;
+
+/// Stats seen fewer times than this are too noisy to call weak.
+const MIN_SAMPLES: f64 = 3.0;
+
+/// Errors and typing speed for a key, bigram or word.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stat {
+    pub count: f64,
+    pub errors: f64,
+    /// Samples that have a latency, the first key of a run doesn't.
+    pub timed: f64,
+    /// Total seconds per character.
+    pub latency: f64,
+}
+
+impl Stat {
+    fn add(&mut self, correct: bool, latency: Option<Duration>) {
+        self.count += 1.0;
+
+        if !correct {
+            self.errors += 1.0;
+        }
+
+        if let Some(latency) = latency {
+            self.timed += 1.0;
+            self.latency += latency.as_secs_f64();
+        }
+    }
+
+    fn decay(&mut self) {
+        self.count *= DECAY;
+        self.errors *= DECAY;
+        self.timed *= DECAY;
+        self.latency *= DECAY;
+    }
+
+    /// Error rate, pulled towards zero while there are few samples.
+    pub fn error_rate(&self) -> f64 {
+        self.errors / (self.count + 2.0)
+    }
+
+    pub fn avg_latency(&self) -> Option<f64> {
+        (self.timed > 0.0).then(|| self.latency / self.timed)
+    }
+
+    /// How much this should be practiced, 1.0 for nothing special.
+    fn weight(&self, avg_latency: Option<f64>) -> f64 {
+        let slowness = match (self.avg_latency(), avg_latency) {
+            (Some(latency), Some(avg)) if avg > 0.0 => (latency / avg - 1.0).max(0.0),
+            _ => 0.0,
+        };
+
+        1.0 + 8.0 * self.error_rate() + 2.0 * slowness
+    }
+
+    fn merge(&mut self, other: &Stat) {
+        self.count += other.count;
+        self.errors += other.errors;
+        self.timed += other.timed;
+        self.latency += other.latency;
+    }
+
+    fn serialize(&self) -> String {
+        format!("{:.3}\t{:.3}\t{:.3}\t{:.4}", self.count, self.errors, self.timed, self.latency)
+    }
+
+    fn parse<'a>(fields: &mut impl Iterator<Item = &'a str>) -> Option<Self> {
+        Some(Stat {
+            count: fields.next()?.parse().ok()?,
+            errors: fields.next()?.parse().ok()?,
+            timed: fields.next()?.parse().ok()?,
+            latency: fields.next()?.parse().ok()?,
+        })
+    }
+}
+
+/// Per key, bigram and word stats gathered from every run, used to
+/// build drills out of the weakest ones.
+///
+/// Stored as `kind\titem\tcount\terrors\ttimed\tlatency` lines, where
+/// kind is `key`, `bigram` or `word`.
+#[derive(Debug, Clone, Default)]
+pub struct PracticeStats {
+    pub keys: BTreeMap<char, Stat>,
+    pub bigrams: BTreeMap<String, Stat>,
+    pub words: BTreeMap<String, Stat>,
+}
+
+impl PracticeStats {
+    pub fn load() -> io::Result<Self> {
+        let path = storage::data_dir().join(PRACTICE_FILE);
+
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(PracticeStats::parse(&content)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(PracticeStats::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(storage::data_path(PRACTICE_FILE)?, self.serialize())
+    }
+
+    fn serialize(&self) -> String {
+        let keys = self.keys.iter()
+            .map(|(key, stat)| ("key", key.to_string(), stat));
+        let bigrams = self.bigrams.iter()
+            .map(|(bigram, stat)| ("bigram", bigram.clone(), stat));
+        let words = self.words.iter()
+            .map(|(word, stat)| ("word", word.clone(), stat));
+
+        keys.chain(bigrams).chain(words)
+            .map(|(kind, item, stat)| format!("{kind}\t{item}\t{}\n", stat.serialize()))
+            .collect()
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut stats = PracticeStats::default();
+
+        for line in content.lines() {
+            let mut fields = line.split('\t');
+            let (Some(kind), Some(item)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let Some(stat) = Stat::parse(&mut fields) else {
+                continue;
+            };
+
+            match kind {
+                "key" => {
+                    if let Some(key) = item.chars().next() {
+                        stats.keys.insert(key, stat);
+                    }
+                },
+                "bigram" => { stats.bigrams.insert(item.to_string(), stat); },
+                "word" => { stats.words.insert(item.to_string(), stat); },
+                _ => {},
+            }
+        }
+
+        stats
+    }
+
+    /// Fold the events of a finished run over `words` into the stats.
+    pub fn record_run(&mut self, words: &[String], events: &[SessionEvent]) {
+        self.keys.values_mut()
+            .chain(self.bigrams.values_mut())
+            .chain(self.words.values_mut())
+            .for_each(Stat::decay);
+
+        // Expected key, time and whether it was right, of the last keystroke.
+        let mut previous: Option<(char, Duration, bool)> = None;
+        let mut word_start = None;
+        let mut word_errors = 0;
+
+        for event in events {
+            match event {
+                SessionEvent::Keystroke { typed, expected, correct, at } => {
+                    let latency = previous.map(|(_, last, _)| at.saturating_sub(last));
+                    word_start = word_start.or(previous.map(|(_, last, _)| last)).or(Some(*at));
+
+                    if let Some(key) = expected.filter(|key| *key != ' ') {
+                        self.keys.entry(key).or_default().add(*correct, latency);
+
+                        if let Some((last, _, true)) = previous && last != ' ' {
+                            self.bigrams.entry(format!("{last}{key}"))
+                                .or_default()
+                                .add(*correct, latency);
+                        }
+                    }
+
+                    if !correct {
+                        word_errors += 1;
+                    }
+
+                    previous = Some((expected.unwrap_or(*typed), *at, *correct));
+                },
+                SessionEvent::WordCompleted { index, correct } => {
+                    let Some(word) = words.get(*index) else {
+                        continue;
+                    };
+
+                    let chars = word.chars().count().max(1) as u32;
+                    let latency = word_start.zip(previous)
+                        .map(|(start, (_, end, _))| end.saturating_sub(start) / chars);
+
+                    self.words.entry(word.clone())
+                        .or_default()
+                        .add(*correct && word_errors == 0, latency);
+
+                    word_start = None;
+                    word_errors = 0;
+                },
+                SessionEvent::WordReopened(_) | SessionEvent::Finished => {},
+            }
+        }
+    }
+
+    /// Key stats summed up per finger of the layout. Keys that aren't on
+    /// the layout are left out.
+    pub fn finger_stats(&self, layout: &KeyboardLayout) -> BTreeMap<Finger, Stat> {
+        let mut fingers = BTreeMap::new();
+
+        for (key, stat) in &self.keys {
+            if let Some(finger) = layout.finger(*key) {
+                fingers.entry(finger).or_insert_with(Stat::default).merge(stat);
+            }
+        }
+
+        fingers
+    }
+
+    fn avg_key_latency(&self) -> Option<f64> {
+        let latencies: Vec<f64> = self.keys.values()
+            .filter_map(Stat::avg_latency)
+            .collect();
+
+        (!latencies.is_empty()).then(|| latencies.iter().sum::<f64>() / latencies.len() as f64)
+    }
+
+    fn key_weight(&self, key: char, avg: Option<f64>) -> f64 {
+        self.keys.get(&key).map_or(1.0, |stat| stat.weight(avg))
+    }
+
+    fn bigram_weight(&self, bigram: &str, avg: Option<f64>) -> f64 {
+        self.bigrams.get(bigram).map_or(1.0, |stat| stat.weight(avg))
+    }
+
+    /// How much a word exercises the weak spots.
+    fn word_weight(&self, word: &str, avg: Option<f64>) -> f64 {
+        let chars: Vec<char> = word.chars().collect();
+        let keys = chars.iter()
+            .map(|key| self.key_weight(*key, avg))
+            .sum::<f64>() / chars.len().max(1) as f64;
+        let bigrams = chars.windows(2)
+            .map(|pair| self.bigram_weight(&pair.iter().collect::<String>(), avg))
+            .fold(1.0, f64::max);
+        let own = self.words.get(word).map_or(1.0, |stat| stat.weight(avg));
+
+        // Squared so weak spots stand out against the many fine words.
+        (keys * bigrams * own).powi(2)
+    }
+
+    /// Text of `count` words weighted towards the weakest keys, bigrams
+    /// and words.
+    pub fn drill(&self, rng: &mut Rng, count: usize) -> String {
+        let avg = self.avg_key_latency();
+        let pool: Vec<&str> = drill::common_words().into_iter()
+            .chain(self.words.keys().map(String::as_str))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let weights: Vec<f64> = pool.iter()
+            .map(|word| self.word_weight(word, avg))
+            .collect();
+
+        let mut words: Vec<&str> = Vec::with_capacity(count);
+
+        while words.len() < count {
+            let word = pool[rng.weighted(&weights)];
+
+            if pool.len() > 1 && words.last() == Some(&word) {
+                continue;
+            }
+
+            words.push(word);
+        }
+
+        words.join(" ")
+    }
+
+    fn weakest<'a, T: 'a>(
+        &self,
+        stats: impl Iterator<Item = (T, &'a Stat)>,
+        limit: usize,
+    ) -> Vec<T> {
+        let avg = self.avg_key_latency();
+        let mut weak: Vec<(T, f64)> = stats
+            .filter(|(_, stat)| stat.count >= MIN_SAMPLES)
+            .map(|(item, stat)| (item, stat.weight(avg)))
+            .filter(|(_, weight)| *weight > 1.5)
+            .collect();
+
+        weak.sort_by(|a, b| b.1.total_cmp(&a.1));
+        weak.into_iter().take(limit).map(|(item, _)| item).collect()
+    }
+
+    /// Short summary of what to practice, empty when nothing stands out.
+    pub fn advice(&self) -> String {
+        let keys = self.weakest(self.keys.iter().map(|(key, stat)| (key.to_string(), stat)), 5);
+        let bigrams = self.weakest(self.bigrams.iter().map(|(b, stat)| (b.clone(), stat)), 3);
+        let words = self.weakest(self.words.iter().map(|(w, stat)| (w.clone(), stat)), 3);
+
+        [("keys", keys), ("bigrams", bigrams), ("words", words)].into_iter()
+            .filter(|(_, items)| !items.is_empty())
+            .map(|(kind, items)| format!("{kind} {}", items.join(" ")))
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stamba::engine::{Key, SessionOptions, TypingSession};
+    use std::time::Instant;
+
+    fn run(text: &str, typed: &str) -> PracticeStats {
+        let now = Instant::now();
+        let mut session = TypingSession::new(text, SessionOptions::default());
+        session.start(now);
+
+        let mut events = Vec::new();
+
+        for (index, c) in typed.chars().enumerate() {
+            let at = now + Duration::from_millis(100 * index as u64);
+            let key = if c == '<' { Key::Backspace } else { Key::Char(c) };
+            events.extend(session.input(key, at));
+        }
+
+        let mut stats = PracticeStats::default();
+        stats.record_run(session.words(), &events);
+        stats
+    }
+
+    #[test]
+    fn records_keys_bigrams_and_words() {
+        let stats = run("ab cd", "ax<b cd");
+
+        assert_eq!(stats.keys[&'a'].count, 1.0);
+        assert_eq!(stats.keys[&'b'].count, 2.0);
+        assert_eq!(stats.keys[&'b'].errors, 1.0);
+        assert_eq!(stats.bigrams["ab"].errors, 1.0);
+        assert_eq!(stats.bigrams["cd"].errors, 0.0);
+        assert_eq!(stats.words["ab"].errors, 1.0);
+        assert_eq!(stats.words["cd"].errors, 0.0);
+        assert!(!stats.bigrams.contains_key("bc"));
+
+        let fingers = stats.finger_stats(&KeyboardLayout::default());
+        assert_eq!(fingers[&Finger::LeftIndex].count, 2.0);
+        assert_eq!(fingers[&Finger::LeftMiddle].count, 2.0);
+        assert_eq!(fingers[&Finger::LeftPinky].count, 1.0);
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let stats = run("ab cd", "ax<b cd");
+        let parsed = PracticeStats::parse(&stats.serialize());
+
+        assert_eq!(parsed.serialize(), stats.serialize());
+        assert_eq!(parsed.words.len(), 2);
+    }
+
+    #[test]
+    fn drill_favors_weak_keys() {
+        let mut stats = PracticeStats::default();
+        stats.keys.insert('z', Stat { count: 10.0, errors: 8.0, ..Default::default() });
+
+        let drill = stats.drill(&mut Rng::new(7), 200);
+        let with_z = drill.split(' ').filter(|word| word.contains('z')).count();
+        let plain = PracticeStats::default().drill(&mut Rng::new(7), 200);
+        let plain_z = plain.split(' ').filter(|word| word.contains('z')).count();
+
+        assert!(with_z > plain_z * 3, "{with_z} vs {plain_z}");
+        assert_eq!(stats.advice(), "keys z");
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+pub mod protocol;
+pub use protocol::{RaceEvent, RaceRequest};
+
+pub mod server;
+pub use server::RaceServer;
+
+pub mod client;
+pub use client::RaceClient;
+
+pub type RaceReader = Box<dyn AsyncRead + Unpin + Send>;
+pub type RaceWriter = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// Where a race lobby listens: `host:port` (or `tcp:host:port`) for TCP
+/// and `unix:/path/to/socket` for a Unix socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaceAddr {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl FromStr for RaceAddr {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = value.strip_prefix("unix:") {
+            return Ok(RaceAddr::Unix(PathBuf::from(path)));
+        }
+
+        let addr = value.strip_prefix("tcp:").unwrap_or(value);
+
+        if !addr.contains(':') {
+            return Err(format!("expected host:port or unix:path, got \"{value}\""));
+        }
+
+        Ok(RaceAddr::Tcp(addr.to_string()))
+    }
+}
+
+impl fmt::Display for RaceAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaceAddr::Tcp(addr) => write!(f, "tcp:{addr}"),
+            RaceAddr::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl RaceAddr {
+    pub async fn connect(&self) -> io::Result<(RaceReader, RaceWriter)> {
+        match self {
+            RaceAddr::Tcp(addr) => {
+                let stream = tokio::net::TcpStream::connect(addr).await?;
+                let (reader, writer) = stream.into_split();
+                Ok((Box::new(reader), Box::new(writer)))
+            },
+            #[cfg(unix)]
+            RaceAddr::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path).await?;
+                let (reader, writer) = stream.into_split();
+                Ok((Box::new(reader), Box::new(writer)))
+            },
+            #[cfg(not(unix))]
+            RaceAddr::Unix(_) => Err(unix_unsupported()),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn unix_unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "unix sockets are not supported")
+}
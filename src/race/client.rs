@@ -0,0 +1,86 @@
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+use crate::events::{AppEvent, AppEventDispatcher};
+use super::{RaceAddr, RaceEvent, RaceRequest};
+
+/// Connection to a race lobby. Events from the lobby are dispatched as
+/// `AppEvent::Race`, the connection closes when the client is dropped.
+#[derive(Debug)]
+pub struct RaceClient {
+    sender: mpsc::UnboundedSender<RaceRequest>,
+}
+
+impl RaceClient {
+    pub fn connect(addr: RaceAddr, name: String, dispatcher: AppEventDispatcher) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run_client(addr, name, receiver, dispatcher));
+
+        RaceClient { sender }
+    }
+
+    pub fn send(&self, request: RaceRequest) {
+        let _ = self.sender.send(request);
+    }
+}
+
+async fn run_client(
+    addr: RaceAddr,
+    name: String,
+    mut requests: mpsc::UnboundedReceiver<RaceRequest>,
+    dispatcher: AppEventDispatcher,
+) {
+    let disconnected = |reason: String| {
+        AppEvent::Race(RaceEvent::Disconnected(reason))
+    };
+
+    let (reader, mut writer) = match addr.connect().await {
+        Ok(streams) => streams,
+        Err(err) => {
+            dispatcher.dispatch(disconnected(format!("Could not connect to {addr}: {err}")));
+            return;
+        },
+    };
+
+    let mut lines = BufReader::new(reader).lines();
+    let join = RaceRequest::Join(name).encode();
+
+    if let Err(err) = writer.write_all(format!("{join}\n").as_bytes()).await {
+        dispatcher.dispatch(disconnected(err.to_string()));
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                match line {
+                    Ok(Some(line)) => {
+                        if let Some(event) = RaceEvent::decode(&line) {
+                            dispatcher.dispatch(AppEvent::Race(event));
+                        }
+                    },
+                    Ok(None) => {
+                        dispatcher.dispatch(disconnected("Lobby closed".to_string()));
+                        break;
+                    },
+                    Err(err) => {
+                        dispatcher.dispatch(disconnected(err.to_string()));
+                        break;
+                    },
+                }
+            }
+            request = requests.recv() => {
+                let Some(request) = request else {
+                    break;
+                };
+
+                let line = format!("{}\n", request.encode());
+
+                if let Err(err) = writer.write_all(line.as_bytes()).await {
+                    dispatcher.dispatch(disconnected(err.to_string()));
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+/// Messages sent from a racer to the lobby.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaceRequest {
+    Join(String),
+    Start,
+    Progress(usize),
+    Finish(Duration),
+}
+
+/// Messages sent from the lobby to the racers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaceEvent {
+    Welcome { id: u32, text: String },
+    Joined { id: u32, name: String },
+    Left(u32),
+    Countdown(u32),
+    Start,
+    Progress { id: u32, words: usize },
+    Finished { id: u32, time: Duration },
+    /// Connection to the lobby was lost, never sent over the wire.
+    Disconnected(String),
+}
+
+/// Collapse line breaks and other whitespace, every message is one line.
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl RaceRequest {
+    pub fn encode(&self) -> String {
+        match self {
+            RaceRequest::Join(name) => format!("JOIN {}", one_line(name)),
+            RaceRequest::Start => "START".to_string(),
+            RaceRequest::Progress(words) => format!("PROGRESS {words}"),
+            RaceRequest::Finish(time) => format!("FINISH {}", time.as_millis()),
+        }
+    }
+
+    pub fn decode(line: &str) -> Option<Self> {
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+
+        let request = match command {
+            "JOIN" => RaceRequest::Join(rest.to_string()),
+            "START" => RaceRequest::Start,
+            "PROGRESS" => RaceRequest::Progress(rest.parse().ok()?),
+            "FINISH" => RaceRequest::Finish(Duration::from_millis(rest.parse().ok()?)),
+            _ => return None,
+        };
+
+        Some(request)
+    }
+}
+
+impl RaceEvent {
+    pub fn encode(&self) -> Option<String> {
+        let line = match self {
+            RaceEvent::Welcome { id, text } => format!("WELCOME {id} {}", one_line(text)),
+            RaceEvent::Joined { id, name } => format!("JOINED {id} {}", one_line(name)),
+            RaceEvent::Left(id) => format!("LEFT {id}"),
+            RaceEvent::Countdown(secs) => format!("COUNTDOWN {secs}"),
+            RaceEvent::Start => "GO".to_string(),
+            RaceEvent::Progress { id, words } => format!("PROGRESS {id} {words}"),
+            RaceEvent::Finished { id, time } => {
+                format!("FINISHED {id} {}", time.as_millis())
+            },
+            RaceEvent::Disconnected(_) => return None,
+        };
+
+        Some(line)
+    }
+
+    pub fn decode(line: &str) -> Option<Self> {
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let (first, second) = rest.split_once(' ').unwrap_or((rest, ""));
+
+        let event = match command {
+            "WELCOME" => RaceEvent::Welcome {
+                id: first.parse().ok()?,
+                text: second.to_string(),
+            },
+            "JOINED" => RaceEvent::Joined {
+                id: first.parse().ok()?,
+                name: second.to_string(),
+            },
+            "LEFT" => RaceEvent::Left(rest.parse().ok()?),
+            "COUNTDOWN" => RaceEvent::Countdown(rest.parse().ok()?),
+            "GO" => RaceEvent::Start,
+            "PROGRESS" => RaceEvent::Progress {
+                id: first.parse().ok()?,
+                words: second.parse().ok()?,
+            },
+            "FINISHED" => RaceEvent::Finished {
+                id: first.parse().ok()?,
+                time: Duration::from_millis(second.parse().ok()?),
+            },
+            _ => return None,
+        };
+
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_texts_stay_on_one_line() {
+        let join = RaceRequest::Join("eve\nSTART".to_string()).encode();
+        assert_eq!(join, "JOIN eve START");
+        assert_eq!(RaceRequest::decode(&join), Some(RaceRequest::Join("eve START".to_string())));
+
+        let welcome = RaceEvent::Welcome { id: 1, text: "fn main() {\n    go();\n}".to_string() };
+        assert_eq!(welcome.encode().unwrap(), "WELCOME 1 fn main() { go(); }");
+
+        let joined = RaceEvent::Joined { id: 2, name: "a\r\nGO".to_string() };
+        assert_eq!(joined.encode().unwrap(), "JOINED 2 a GO");
+    }
+}
@@ -0,0 +1,461 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+use super::{RaceAddr, RaceEvent, RaceReader, RaceRequest, RaceWriter};
+
+const COUNTDOWN_SECS: u32 = 3;
+
+/// Lobby that hands out the text and relays progress between racers.
+#[derive(Debug)]
+pub struct RaceServer {
+    listener: Listener,
+    text: String,
+}
+
+#[derive(Debug)]
+enum Listener {
+    Tcp(tokio::net::TcpListener),
+    /// Removes its socket file when dropped.
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, std::path::PathBuf),
+}
+
+#[cfg(unix)]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Listener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ServerEvent {
+    Connected(u32, mpsc::UnboundedSender<RaceEvent>),
+    Request(u32, RaceRequest),
+    Disconnected(u32),
+    /// Seconds left in the given countdown round.
+    Countdown(u32, u32),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum LobbyState {
+    Waiting,
+    Countdown,
+    Racing,
+}
+
+#[derive(Debug)]
+struct Racer {
+    name: Option<String>,
+    /// Done with the current race, or joined after it started and only
+    /// watches.
+    finished: bool,
+    sender: mpsc::UnboundedSender<RaceEvent>,
+}
+
+#[derive(Debug)]
+struct Lobby {
+    text: String,
+    state: LobbyState,
+    racers: BTreeMap<u32, Racer>,
+    /// Countdowns from earlier rounds are ignored once this moves on.
+    round: u32,
+    events: mpsc::UnboundedSender<ServerEvent>,
+}
+
+impl RaceServer {
+    pub async fn bind(addr: &RaceAddr, text: String) -> io::Result<Self> {
+        let listener = match addr {
+            RaceAddr::Tcp(addr) => {
+                Listener::Tcp(tokio::net::TcpListener::bind(addr).await?)
+            },
+            #[cfg(unix)]
+            RaceAddr::Unix(path) => {
+                remove_stale_socket(path)?;
+                Listener::Unix(tokio::net::UnixListener::bind(path)?, path.clone())
+            },
+            #[cfg(not(unix))]
+            RaceAddr::Unix(_) => return Err(super::unix_unsupported()),
+        };
+
+        Ok(RaceServer { listener, text })
+    }
+
+    async fn accept(&self) -> io::Result<(RaceReader, RaceWriter)> {
+        match &self.listener {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+                let (reader, writer) = stream.into_split();
+                Ok((Box::new(reader), Box::new(writer)))
+            },
+            #[cfg(unix)]
+            Listener::Unix(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+                let (reader, writer) = stream.into_split();
+                Ok((Box::new(reader), Box::new(writer)))
+            },
+        }
+    }
+
+    /// Accept racers until the task is dropped, a unix socket file goes
+    /// away with it.
+    pub async fn run(self) {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut lobby = Lobby::new(self.text.clone(), sender.clone());
+        let mut next_id = 0;
+
+        loop {
+            tokio::select! {
+                accepted = self.accept() => {
+                    if let Ok((reader, writer)) = accepted {
+                        tokio::spawn(connection(next_id, reader, writer, sender.clone()));
+                        next_id += 1;
+                    }
+                }
+                Some(event) = receiver.recv() => {
+                    lobby.handle(event);
+                }
+            }
+        }
+    }
+}
+
+/// A socket left behind by an earlier lobby blocks binding, anything else
+/// at the path is not ours to delete.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+
+    std::fs::remove_file(path)
+}
+
+async fn connection(
+    id: u32,
+    reader: RaceReader,
+    mut writer: RaceWriter,
+    events: mpsc::UnboundedSender<ServerEvent>,
+) {
+    let (sender, mut outgoing) = mpsc::unbounded_channel::<RaceEvent>();
+    let mut lines = BufReader::new(reader).lines();
+
+    if events.send(ServerEvent::Connected(id, sender)).is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Ok(Some(line)) = line else {
+                    break;
+                };
+
+                if let Some(request) = RaceRequest::decode(&line)
+                    && events.send(ServerEvent::Request(id, request)).is_err() {
+                    break;
+                }
+            }
+            Some(event) = outgoing.recv() => {
+                let Some(line) = event.encode() else {
+                    continue;
+                };
+
+                if writer.write_all(format!("{line}\n").as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = events.send(ServerEvent::Disconnected(id));
+}
+
+impl Lobby {
+    fn new(text: String, events: mpsc::UnboundedSender<ServerEvent>) -> Self {
+        Lobby {
+            text,
+            state: LobbyState::Waiting,
+            racers: BTreeMap::new(),
+            round: 0,
+            events,
+        }
+    }
+
+    fn send(&self, id: u32, event: RaceEvent) {
+        if let Some(racer) = self.racers.get(&id) {
+            let _ = racer.sender.send(event);
+        }
+    }
+
+    fn broadcast(&self, event: RaceEvent) {
+        for racer in self.racers.values().filter(|racer| racer.name.is_some()) {
+            let _ = racer.sender.send(event.clone());
+        }
+    }
+
+    fn handle(&mut self, event: ServerEvent) {
+        match event {
+            ServerEvent::Connected(id, sender) => {
+                let racer = Racer {
+                    name: None,
+                    finished: false,
+                    sender,
+                };
+
+                self.racers.insert(id, racer);
+            },
+            ServerEvent::Disconnected(id) => {
+                if self.racers.remove(&id).is_some() {
+                    self.broadcast(RaceEvent::Left(id));
+                    self.check_finished();
+                }
+
+                if self.racers.values().all(|racer| racer.name.is_none()) {
+                    self.state = LobbyState::Waiting;
+                    self.round += 1;
+                }
+            },
+            ServerEvent::Countdown(round, _) if round != self.round => {},
+            ServerEvent::Countdown(_, 0) => {
+                self.state = LobbyState::Racing;
+
+                for racer in self.racers.values_mut() {
+                    racer.finished = false;
+                }
+
+                self.broadcast(RaceEvent::Start);
+            },
+            ServerEvent::Countdown(_, secs) => {
+                self.broadcast(RaceEvent::Countdown(secs));
+            },
+            ServerEvent::Request(id, request) => {
+                self.handle_request(id, request);
+            },
+        }
+    }
+
+    fn handle_request(&mut self, id: u32, request: RaceRequest) {
+        match request {
+            RaceRequest::Join(name) => {
+                let text = self.text.clone();
+                self.send(id, RaceEvent::Welcome { id, text });
+
+                for (&other, racer) in &self.racers {
+                    if let Some(name) = &racer.name {
+                        let name = name.clone();
+                        self.send(id, RaceEvent::Joined { id: other, name });
+                    }
+                }
+
+                // Nothing to type until the next race.
+                let late = self.state == LobbyState::Racing;
+
+                if let Some(racer) = self.racers.get_mut(&id) {
+                    racer.name = Some(name.clone());
+                    racer.finished = late;
+                }
+
+                self.broadcast(RaceEvent::Joined { id, name });
+            },
+            RaceRequest::Start if self.state == LobbyState::Waiting => {
+                self.state = LobbyState::Countdown;
+                self.round += 1;
+                tokio::spawn(countdown(self.round, self.events.clone()));
+            },
+            RaceRequest::Start => {},
+            RaceRequest::Progress(words) => {
+                self.broadcast(RaceEvent::Progress { id, words });
+            },
+            RaceRequest::Finish(time) => {
+                if let Some(racer) = self.racers.get_mut(&id) {
+                    racer.finished = true;
+                }
+
+                self.broadcast(RaceEvent::Finished { id, time });
+                self.check_finished();
+            },
+        }
+    }
+
+    /// Open the lobby for another race once everyone is done.
+    fn check_finished(&mut self) {
+        let all_finished = self.racers.values()
+            .filter(|racer| racer.name.is_some())
+            .all(|racer| racer.finished);
+
+        if self.state == LobbyState::Racing && all_finished {
+            self.state = LobbyState::Waiting;
+        }
+    }
+}
+
+async fn countdown(round: u32, events: mpsc::UnboundedSender<ServerEvent>) {
+    for secs in (0..=COUNTDOWN_SECS).rev() {
+        if events.send(ServerEvent::Countdown(round, secs)).is_err() {
+            return;
+        }
+
+        if secs > 0 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+
+    type Racer = (Lines<BufReader<RaceReader>>, RaceWriter);
+
+    async fn join(addr: &RaceAddr, name: &str) -> Racer {
+        let (reader, mut writer) = addr.connect().await.unwrap();
+        let join = RaceRequest::Join(name.to_string()).encode();
+        writer.write_all(format!("{join}\n").as_bytes()).await.unwrap();
+
+        (BufReader::new(reader).lines(), writer)
+    }
+
+    async fn next_event(racer: &mut Racer) -> RaceEvent {
+        let line = racer.0.next_line().await.unwrap().unwrap();
+        RaceEvent::decode(&line).unwrap()
+    }
+
+    async fn send(racer: &mut Racer, request: RaceRequest) {
+        let line = format!("{}\n", request.encode());
+        racer.1.write_all(line.as_bytes()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn race_over_unix_socket() {
+        let path = std::env::temp_dir()
+            .join(format!("stamba-race-{}.sock", std::process::id()));
+        let addr = RaceAddr::Unix(path.clone());
+        let server = RaceServer::bind(&addr, "hello world".to_string()).await.unwrap();
+        let server = tokio::spawn(server.run());
+
+        let mut alice = join(&addr, "alice").await;
+        let text = "hello world".to_string();
+        assert_eq!(next_event(&mut alice).await, RaceEvent::Welcome { id: 0, text });
+        assert_eq!(next_event(&mut alice).await, RaceEvent::Joined {
+            id: 0,
+            name: "alice".to_string(),
+        });
+
+        let mut bob = join(&addr, "bob").await;
+        let text = "hello world".to_string();
+        assert_eq!(next_event(&mut bob).await, RaceEvent::Welcome { id: 1, text });
+        assert_eq!(next_event(&mut bob).await, RaceEvent::Joined {
+            id: 0,
+            name: "alice".to_string(),
+        });
+
+        let bob_joined = RaceEvent::Joined { id: 1, name: "bob".to_string() };
+        assert_eq!(next_event(&mut bob).await, bob_joined);
+        assert_eq!(next_event(&mut alice).await, bob_joined);
+
+        send(&mut alice, RaceRequest::Start).await;
+        assert_eq!(next_event(&mut alice).await, RaceEvent::Countdown(3));
+        assert_eq!(next_event(&mut bob).await, RaceEvent::Countdown(3));
+
+        send(&mut bob, RaceRequest::Progress(1)).await;
+        let progress = RaceEvent::Progress { id: 1, words: 1 };
+        assert_eq!(next_event(&mut alice).await, progress);
+        assert_eq!(next_event(&mut bob).await, progress);
+
+        drop(bob);
+        assert_eq!(next_event(&mut alice).await, RaceEvent::Left(1));
+
+        server.abort();
+        let _ = server.await;
+        assert!(!path.exists(), "socket is removed with the server");
+    }
+
+    #[tokio::test]
+    async fn keeps_files_that_are_not_sockets() {
+        let path = std::env::temp_dir()
+            .join(format!("stamba-race-{}.txt", std::process::id()));
+        std::fs::write(&path, "notes").unwrap();
+
+        let addr = RaceAddr::Unix(path.clone());
+        let err = RaceServer::bind(&addr, "hello".to_string()).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "notes");
+
+        std::fs::remove_file(&path).unwrap();
+
+        // A stale socket is replaced.
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let server = RaceServer::bind(&addr, "hello".to_string()).await.unwrap();
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    fn lobby_racer(lobby: &mut Lobby, id: u32, name: &str) -> mpsc::UnboundedReceiver<RaceEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        lobby.handle(ServerEvent::Connected(id, sender));
+        lobby.handle(ServerEvent::Request(id, RaceRequest::Join(name.to_string())));
+        receiver
+    }
+
+    fn received(receiver: &mut mpsc::UnboundedReceiver<RaceEvent>) -> Vec<RaceEvent> {
+        std::iter::from_fn(|| receiver.try_recv().ok()).collect()
+    }
+
+    #[tokio::test]
+    async fn late_joiner_watches_until_the_rematch() {
+        let (events, _) = mpsc::unbounded_channel();
+        let mut lobby = Lobby::new("ab cd".to_string(), events);
+
+        let _alice = lobby_racer(&mut lobby, 0, "alice");
+        lobby.handle(ServerEvent::Request(0, RaceRequest::Start));
+        lobby.handle(ServerEvent::Countdown(lobby.round, 0));
+        assert_eq!(lobby.state, LobbyState::Racing);
+
+        let mut bob = lobby_racer(&mut lobby, 1, "bob");
+        lobby.handle(ServerEvent::Request(0, RaceRequest::Finish(Duration::from_secs(3))));
+        assert_eq!(lobby.state, LobbyState::Waiting, "bob joined late and doesn't hold up the lobby");
+
+        received(&mut bob);
+        lobby.handle(ServerEvent::Request(1, RaceRequest::Start));
+        assert_eq!(lobby.state, LobbyState::Countdown);
+        lobby.handle(ServerEvent::Countdown(lobby.round, 0));
+        assert_eq!(received(&mut bob), [RaceEvent::Start]);
+        assert!(!lobby.racers[&1].finished);
+    }
+
+    #[tokio::test]
+    async fn empty_lobby_goes_back_to_waiting() {
+        let (events, _) = mpsc::unbounded_channel();
+        let mut lobby = Lobby::new("ab cd".to_string(), events);
+
+        let _alice = lobby_racer(&mut lobby, 0, "alice");
+        lobby.handle(ServerEvent::Request(0, RaceRequest::Start));
+        let round = lobby.round;
+        lobby.handle(ServerEvent::Disconnected(0));
+        assert_eq!(lobby.state, LobbyState::Waiting);
+
+        // The countdown of the abandoned round doesn't start a race.
+        let mut bob = lobby_racer(&mut lobby, 1, "bob");
+        lobby.handle(ServerEvent::Countdown(round, 0));
+        assert_eq!(lobby.state, LobbyState::Waiting);
+        assert!(!received(&mut bob).contains(&RaceEvent::Start));
+    }
+}
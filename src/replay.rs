@@ -0,0 +1,330 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use stamba::engine::{SessionOptions, SessionResults, TypingSession};
+
+use crate::keymap::session_key;
+use crate::settings::ErrorMode;
+
+const REPLAY_MAGIC: &str = "stamba-replay 1";
+
+/// Key event recorded at an offset from the start of the run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayKey {
+    pub at: Duration,
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl ReplayKey {
+    pub fn key_event(&self) -> KeyEvent {
+        KeyEvent::new(self.code, self.modifiers)
+    }
+}
+
+/// Keystrokes of a single run, along with everything needed to play
+/// it back.
+///
+/// The file format is line based:
+///
+/// ```text
+/// stamba-replay 1
+/// mode normal
+/// backtrack 0
+/// text Hello world!
+/// limit 30
+/// <ms> <modifier bits> <key>
+/// ```
+///
+/// `limit` is only there for timed runs, in seconds.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub text: String,
+    pub error_mode: ErrorMode,
+    pub backtrack: bool,
+    /// Time limit of a timed run.
+    pub limit: Option<Duration>,
+    pub keys: Vec<ReplayKey>,
+}
+
+impl Replay {
+    pub fn new(text: String, error_mode: ErrorMode, backtrack: bool) -> Self {
+        Replay {
+            text,
+            error_mode,
+            backtrack,
+            limit: None,
+            keys: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, at: Duration, key: &KeyEvent) {
+        if encode_key(key.code).is_none() {
+            return;
+        }
+
+        self.keys.push(ReplayKey {
+            at,
+            code: key.code,
+            modifiers: key.modifiers,
+        });
+    }
+
+    /// Session options the run was played with.
+    pub fn options(&self) -> SessionOptions {
+        SessionOptions {
+            error_mode: self.error_mode,
+            backtrack: self.backtrack,
+            ..Default::default()
+        }
+    }
+
+    /// Results of playing the keys back, `None` if the run never
+    /// finished.
+    pub fn results(&self) -> Option<SessionResults> {
+        let origin = Instant::now();
+        let mut session = TypingSession::new(&self.text, self.options());
+        session.start(origin);
+
+        for key in &self.keys {
+            if let Some(input) = session_key(&key.key_event()) {
+                session.input(input, origin + key.at);
+            }
+        }
+
+        if let Some(limit) = self.limit {
+            session.stop(origin + limit);
+        }
+
+        session.results()
+    }
+
+    /// Time of the last recorded key.
+    pub fn duration(&self) -> Duration {
+        self.keys.last().map_or(Duration::ZERO, |key| key.at)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+
+        Replay::parse(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.serialize())
+    }
+
+    pub fn serialize(&self) -> String {
+        let mode = self.error_mode.name();
+
+        let mut out = format!(
+            "{REPLAY_MAGIC}\nmode {mode}\nbacktrack {}\ntext {}\n",
+            self.backtrack as u8,
+            self.text.split_whitespace().collect::<Vec<_>>().join(" "),
+        );
+
+        if let Some(limit) = self.limit {
+            out.push_str(&format!("limit {}\n", limit.as_secs()));
+        }
+
+        for key in &self.keys {
+            let Some(code) = encode_key(key.code) else {
+                continue;
+            };
+
+            out.push_str(&format!(
+                "{} {} {code}\n",
+                key.at.as_millis(),
+                key.modifiers.bits(),
+            ));
+        }
+
+        out
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut lines = content.lines();
+
+        if lines.next() != Some(REPLAY_MAGIC) {
+            return Err("not a stamba replay".to_string());
+        }
+
+        let mut replay = Replay::new(String::new(), ErrorMode::Normal, false);
+
+        for (number, line) in lines.enumerate() {
+            let bad_line = || format!("bad replay line {}: {line}", number + 2);
+
+            if let Some(mode) = line.strip_prefix("mode ") {
+                replay.error_mode = mode.parse().map_err(|_| bad_line())?;
+            } else if let Some(backtrack) = line.strip_prefix("backtrack ") {
+                replay.backtrack = backtrack == "1";
+            } else if let Some(text) = line.strip_prefix("text ") {
+                replay.text = text.to_string();
+            } else if let Some(limit) = line.strip_prefix("limit ") {
+                let secs = limit.parse().map_err(|_| bad_line())?;
+                replay.limit = Some(Duration::from_secs(secs));
+            } else if !line.is_empty() {
+                replay.keys.push(parse_key(line).ok_or_else(bad_line)?);
+            }
+        }
+
+        if replay.text.is_empty() {
+            return Err("replay has no text".to_string());
+        }
+
+        Ok(replay)
+    }
+}
+
+/// Stable identifier of a text, used to find earlier runs of it.
+pub fn text_id(text: &str) -> String {
+    // FNV-1a, so the id doesn't change between builds.
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for word in text.split_whitespace() {
+        for byte in word.bytes().chain([b' ']) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    format!("{hash:016x}")
+}
+
+fn parse_key(line: &str) -> Option<ReplayKey> {
+    let mut parts = line.splitn(3, ' ');
+    let at = parts.next()?.parse().ok()?;
+    let modifiers = parts.next()?.parse().ok()?;
+    let code = decode_key(parts.next()?)?;
+
+    Some(ReplayKey {
+        at: Duration::from_millis(at),
+        code,
+        modifiers: KeyModifiers::from_bits_truncate(modifiers),
+    })
+}
+
+fn encode_key(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Char(c) => return Some(format!("c{c}")),
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Delete => "Delete",
+        KeyCode::Enter => "Enter",
+        KeyCode::Left => "Left",
+        KeyCode::Right => "Right",
+        KeyCode::Home => "Home",
+        KeyCode::End => "End",
+        _ => return None,
+    };
+
+    Some(name.to_string())
+}
+
+fn decode_key(key: &str) -> Option<KeyCode> {
+    if let Some(c) = key.strip_prefix('c') {
+        let mut chars = c.chars();
+        let c = chars.next()?;
+
+        return chars.next().is_none().then_some(KeyCode::Char(c));
+    }
+
+    let code = match key {
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        "Enter" => KeyCode::Enter,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn round_trips_keys_and_settings() {
+        let mut replay = Replay::new("Hello   world!".to_string(), ErrorMode::StopOnWord, true);
+        replay.record(Duration::from_millis(120), &key(KeyCode::Char('H'), KeyModifiers::SHIFT));
+        replay.record(Duration::from_millis(250), &key(KeyCode::Char(' '), KeyModifiers::NONE));
+        replay.record(Duration::from_millis(400), &key(KeyCode::Backspace, KeyModifiers::CONTROL));
+        // Not part of the format, so not recorded.
+        replay.record(Duration::from_millis(500), &key(KeyCode::F(1), KeyModifiers::NONE));
+
+        let content = replay.serialize();
+        assert!(content.contains("\n250 0 c \n"), "{content}");
+        assert!(content.contains("text Hello world!\n"));
+
+        let parsed = Replay::parse(&content).unwrap();
+        assert_eq!(parsed.text, "Hello world!");
+        assert_eq!(parsed.error_mode, ErrorMode::StopOnWord);
+        assert!(parsed.backtrack);
+        assert_eq!(parsed.limit, None);
+        assert_eq!(parsed.keys, replay.keys);
+        assert_eq!(parsed.keys[0].modifiers, KeyModifiers::SHIFT);
+        assert_eq!(parsed.keys[2].modifiers, KeyModifiers::CONTROL);
+        assert_eq!(parsed.duration(), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn round_trips_the_time_limit() {
+        let mut replay = Replay::new("a b".to_string(), ErrorMode::Normal, false);
+        replay.limit = Some(Duration::from_secs(30));
+
+        let content = replay.serialize();
+        assert!(content.contains("\nlimit 30\n"), "{content}");
+        assert_eq!(Replay::parse(&content).unwrap().limit, Some(Duration::from_secs(30)));
+        assert!(Replay::parse(&format!("{REPLAY_MAGIC}\ntext hi\nlimit soon\n")).is_err());
+    }
+
+    #[test]
+    fn results_play_the_keys_back() {
+        let mut replay = Replay::new("ab cd".to_string(), ErrorMode::Normal, false);
+        for (ms, c) in [(0, 'a'), (100, 'b'), (200, ' ')] {
+            replay.record(Duration::from_millis(ms), &key(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        assert!(replay.results().is_none());
+
+        replay.limit = Some(Duration::from_secs(6));
+        let results = replay.results().unwrap();
+        assert_eq!(results.words, 1);
+        assert_eq!(results.wpm, 10.0);
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        assert!(Replay::parse("text hi\n").is_err());
+        assert!(Replay::parse(&format!("{REPLAY_MAGIC}\nmode normal\n")).is_err());
+        assert!(Replay::parse(&format!("{REPLAY_MAGIC}\nmode sideways\ntext hi\n")).is_err());
+
+        let err = Replay::parse(&format!("{REPLAY_MAGIC}\ntext hi\n10 0 cab\n")).unwrap_err();
+        assert_eq!(err, "bad replay line 3: 10 0 cab");
+        assert!(Replay::parse(&format!("{REPLAY_MAGIC}\ntext hi\n10 0 F1\n")).is_err());
+        assert!(Replay::parse(&format!("{REPLAY_MAGIC}\ntext hi\nsoon 0 ch\n")).is_err());
+    }
+
+    #[test]
+    fn unknown_modifier_bits_are_dropped() {
+        let replay = Replay::parse(&format!("{REPLAY_MAGIC}\ntext hi\n10 255 ch\n")).unwrap();
+        assert_eq!(replay.keys[0].modifiers, KeyModifiers::all());
+    }
+
+    #[test]
+    fn text_id_ignores_spacing() {
+        assert_eq!(text_id("a  b\nc"), text_id("a b c"));
+        assert_ne!(text_id("a b c"), text_id("a bc"));
+    }
+}
@@ -0,0 +1,180 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+
+pub use stamba::engine::ErrorMode;
+use stamba::engine::SessionOptions;
+
+use crate::layout::KeyboardLayout;
+
+/// `ErrorMode` as picked on the command line, so the engine doesn't
+/// depend on clap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorModeArg {
+    /// Wrong characters are typed and must be corrected before moving on.
+    Normal,
+    /// Wrong characters are rejected and only counted.
+    StopOnLetter,
+    /// Wrong characters are typed, but space won't leave a wrong word.
+    StopOnWord,
+    /// Space always moves to the next word, wrong words are recorded.
+    Free,
+}
+
+impl From<ErrorModeArg> for ErrorMode {
+    fn from(arg: ErrorModeArg) -> Self {
+        match arg {
+            ErrorModeArg::Normal => ErrorMode::Normal,
+            ErrorModeArg::StopOnLetter => ErrorMode::StopOnLetter,
+            ErrorModeArg::StopOnWord => ErrorMode::StopOnWord,
+            ErrorModeArg::Free => ErrorMode::Free,
+        }
+    }
+}
+
+/// Which earlier run of the same text to race against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GhostTarget {
+    /// The fastest finished run.
+    Best,
+    /// The most recent finished run.
+    Last,
+}
+
+/// Length of a words run when none is given.
+const DEFAULT_WORDS: usize = 25;
+
+/// What gets typed and when a run ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    /// Random words until time runs out, in seconds.
+    Timed(u64),
+    /// A number of random words.
+    Words(usize),
+    Quote,
+    Code,
+}
+
+/// Modes with a fixed text, as in `quote`, `code`, `words` or `words:50`.
+/// Timed runs can't be picked this way, races end with the text.
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, count) = value.split_once(':').unwrap_or((value, ""));
+
+        match (name, count) {
+            ("quote", "") => Ok(GameMode::Quote),
+            ("code", "") => Ok(GameMode::Code),
+            ("words", "") => Ok(GameMode::Words(DEFAULT_WORDS)),
+            ("words", count) => match count.parse() {
+                Ok(count) if count > 0 => Ok(GameMode::Words(count)),
+                _ => Err(format!("expected a positive word count, got \"{count}\"")),
+            },
+            _ => Err(format!("expected quote, code, words or words:COUNT, got \"{value}\"")),
+        }
+    }
+}
+
+/// How the caret is drawn in the text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CaretStyle {
+    /// Highlight the next character.
+    #[default]
+    Block,
+    /// Underline the next character in color.
+    Underline,
+    /// A thin bar before the next character.
+    Bar,
+    /// The terminal's own cursor, in whatever shape it is set up with.
+    Terminal,
+}
+
+/// Speed of the pace caret.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pace {
+    Wpm(f64),
+    /// Average WPM of the recorded history.
+    Average,
+}
+
+impl FromStr for Pace {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "avg" {
+            return Ok(Pace::Average);
+        }
+
+        match value.parse::<f64>() {
+            Ok(wpm) if wpm > 0.0 => Ok(Pace::Wpm(wpm)),
+            _ => Err(format!("expected a positive WPM or \"avg\", got \"{value}\"")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    pub error_mode: ErrorMode,
+    /// Allow going back into previous words that contain errors.
+    pub backtrack: bool,
+    /// Only count mistakes that were left uncorrected.
+    pub forgive_corrections: bool,
+    pub ghost: Option<GhostTarget>,
+    pub pace: Option<Pace>,
+    /// Name shown to other racers, defaults to the user name.
+    pub name: Option<String>,
+    pub layout: KeyboardLayout,
+    /// Show an on-screen keyboard while typing.
+    pub keyboard: bool,
+    pub caret: CaretStyle,
+    /// Blink the caret while no keys are pressed.
+    pub blink: bool,
+    /// Type straight onto the text with nothing else on screen.
+    pub focus: bool,
+}
+
+impl Settings {
+    pub fn session_options(&self) -> SessionOptions {
+        SessionOptions {
+            error_mode: self.error_mode,
+            backtrack: self.backtrack,
+            forgive_corrections: self.forgive_corrections,
+        }
+    }
+
+    pub fn player_name(&self) -> String {
+        self.name.clone()
+            .or_else(|| std::env::var("USER").ok())
+            .unwrap_or_else(|| "racer".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_mode_names_match_the_command_line() {
+        for arg in ErrorModeArg::value_variants() {
+            let name = arg.to_possible_value().unwrap().get_name().to_string();
+            let mode = ErrorMode::from(*arg);
+
+            assert_eq!(mode.name(), name);
+            assert_eq!(name.parse::<ErrorMode>(), Ok(mode));
+        }
+
+        assert_eq!(ErrorModeArg::value_variants().len(), ErrorMode::ALL.len());
+        assert!("sideways".parse::<ErrorMode>().is_err());
+    }
+
+    #[test]
+    fn parses_race_modes() {
+        assert_eq!("quote".parse(), Ok(GameMode::Quote));
+        assert_eq!("code".parse(), Ok(GameMode::Code));
+        assert_eq!("words".parse(), Ok(GameMode::Words(25)));
+        assert_eq!("words:50".parse(), Ok(GameMode::Words(50)));
+        assert!("words:0".parse::<GameMode>().is_err());
+        assert!("timed:30".parse::<GameMode>().is_err());
+    }
+}
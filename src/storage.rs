@@ -0,0 +1,54 @@
+use std::env;
+use std::io;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "stamba";
+
+#[cfg(test)]
+thread_local! {
+    static DATA_DIR: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Directory where stamba keeps its data (replays, history and so on).
+///
+/// A directory set with `set_data_dir` takes precedence, then
+/// `STAMBA_DATA_DIR`, `XDG_DATA_HOME` and finally `~/.local/share`.
+pub fn data_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = DATA_DIR.with_borrow(Clone::clone) {
+        return dir;
+    }
+
+    if let Some(dir) = env::var_os("STAMBA_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    if let Some(dir) = env::var_os("XDG_DATA_HOME") {
+        return PathBuf::from(dir).join(APP_DIR);
+    }
+
+    let home = env::var_os("HOME").unwrap_or_else(|| ".".into());
+
+    PathBuf::from(home)
+        .join(".local")
+        .join("share")
+        .join(APP_DIR)
+}
+
+/// Use the given data directory on this thread, so tests running in
+/// parallel don't see each other's files.
+#[cfg(test)]
+pub fn set_data_dir(dir: PathBuf) {
+    DATA_DIR.set(Some(dir));
+}
+
+/// Path inside the data directory, creating missing parent directories.
+pub fn data_path(name: &str) -> io::Result<PathBuf> {
+    let path = data_dir().join(name);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    Ok(path)
+}